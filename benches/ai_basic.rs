@@ -0,0 +1,39 @@
+use hnefatafl_copenhagen::{
+    ai::{AI, AiBasic},
+    game::Game,
+    transposition_table::TranspositionTable,
+};
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+fn ai_basic(depth: u64) -> AiBasic {
+    AiBasic {
+        depth,
+        time_to_move: 600,
+        transposition_table: TranspositionTable::new(16),
+        ..AiBasic::default()
+    }
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let game = Game::default();
+
+    // Each search looks at the same positions, so criterion can report the nodes per second.
+    let mut ai = ai_basic(3);
+    ai.generate_move(&game);
+
+    let mut group = c.benchmark_group("ai_basic");
+    group.throughput(Throughput::Elements(ai.stats.nodes));
+    group.bench_function("depth 3", |b| {
+        b.iter(|| ai_basic(3).generate_move(&game));
+    });
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = criterion_benchmark
+}
+
+criterion_main!(benches);
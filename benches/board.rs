@@ -0,0 +1,62 @@
+use hnefatafl_copenhagen::{
+    board::{Board, StartingPosition},
+    game::PreviousBoards,
+    play::Plae,
+    role::Role,
+    rules::RuleSet,
+    status::Status,
+};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+/// A position some plays into a game from `starting_position`, so there are captures to find.
+fn middle_game(starting_position: StartingPosition) -> (Board, Role) {
+    let rules = RuleSet::default();
+    let mut board = starting_position.board();
+    let mut previous_boards = PreviousBoards::new(&board);
+    let mut turn = Role::Attacker;
+
+    for index in 0..30 {
+        let plays: Vec<_> = board.legal_moves(turn, &previous_boards, &rules).collect();
+        let play = Plae::Play(plays[index * 7 % plays.len()].clone());
+
+        board
+            .play(&play, &Status::Ongoing, &turn, &mut previous_boards, &rules)
+            .expect("the play is legal");
+        turn = turn.opposite();
+    }
+
+    (board, turn)
+}
+
+/// Finds every legal play and plays each of them.
+fn every_play(board: &Board, turn: Role, previous_boards: &PreviousBoards, rules: &RuleSet) {
+    for play in board.legal_moves(turn, previous_boards, rules) {
+        let _ = board.play_internal(
+            &Plae::Play(play),
+            &Status::Ongoing,
+            &turn,
+            previous_boards,
+            rules,
+        );
+    }
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let rules = RuleSet::default();
+
+    for (name, starting_position) in [
+        ("board 11x11", StartingPosition::Copenhagen),
+        ("board 19x19", StartingPosition::AleaEvangelii),
+    ] {
+        let (board, turn) = middle_game(starting_position);
+        let previous_boards = PreviousBoards::new(&board);
+
+        c.bench_function(name, |b| {
+            b.iter(|| every_play(&board, turn, &previous_boards, &rules));
+        });
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
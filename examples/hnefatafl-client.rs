@@ -32,6 +32,7 @@ use hnefatafl_copenhagen::{
     accounts::Email,
     draw::Draw,
     game::{Game, TimeUnix},
+    game_end::GameEnd,
     glicko::{CONFIDENCE_INTERVAL_95, Rating},
     play::{BOARD_LETTERS, Vertex},
     rating::Rated,
@@ -285,6 +286,8 @@ struct Client {
     #[serde(skip)]
     game: Option<Game>,
     #[serde(skip)]
+    game_end: Option<GameEnd>,
+    #[serde(skip)]
     game_id: usize,
     #[serde(skip)]
     games_light: ServerGamesLight,
@@ -377,14 +380,6 @@ impl<'a> Client {
     #[allow(clippy::too_many_lines)]
     #[must_use]
     fn board(&self) -> Row<Message> {
-        let letters: Vec<_> = BOARD_LETTERS.chars().collect();
-        let (board_size, letter_size, piece_size, spacing) = match self.screen_size {
-            Size::Large | Size::Giant => (75, 55, 60, 6),
-            Size::Medium => (65, 45, 50, 8),
-            Size::Small => (55, 35, 40, 11),
-            Size::Tiny => (40, 20, 25, 16),
-        };
-
         let board = if let Some(game_handle) = &self.archived_game_handle {
             &game_handle.boards[game_handle.play]
         } else {
@@ -394,6 +389,23 @@ impl<'a> Client {
             &game.board
         };
 
+        let size = board.size.get();
+        let letters: Vec<_> = BOARD_LETTERS.chars().take(size).collect();
+        let restricted = board.size.masks().restricted;
+        let (board_size, letter_size, piece_size, spacing) = match self.screen_size {
+            Size::Large | Size::Giant => (75, 55, 60, 6),
+            Size::Medium => (65, 45, 50, 8),
+            Size::Small => (55, 35, 40, 11),
+            Size::Tiny => (40, 20, 25, 16),
+        };
+        // Keep the board about as big as an 11×11 board, whatever its size.
+        let squares = u16::try_from(size).expect("a board is at most 19×19");
+        let (board_size, piece_size) = if squares > 11 {
+            (board_size * 11 / squares, piece_size * 11 / squares)
+        } else {
+            (board_size, piece_size)
+        };
+
         let mut game_display = Row::new().spacing(2);
 
         let mut possible_moves = None;
@@ -405,8 +417,8 @@ impl<'a> Client {
 
         let mut column = column![text(" ").size(letter_size)].spacing(spacing);
 
-        for i in 0..11 {
-            let i = 11 - i;
+        for i in 0..size {
+            let i = size - i;
             column = column.push(text!("{i:2}").size(letter_size).align_y(Vertical::Center));
         }
         game_display = game_display.push(column);
@@ -415,17 +427,16 @@ impl<'a> Client {
             let mut column = Column::new().spacing(2).align_x(Horizontal::Center);
             column = column.push(text(letter).size(letter_size));
 
-            for y in 0..11 {
-                let vertex = Vertex { x, y };
+            for y in 0..size {
+                let vertex = Vertex {
+                    size: board.size,
+                    x,
+                    y,
+                };
 
                 let mut text_ = match board.get(&vertex) {
                     Space::Empty => {
-                        if (y, x) == (0, 0)
-                            || (y, x) == (10, 0)
-                            || (y, x) == (0, 10)
-                            || (y, x) == (10, 10)
-                            || (y, x) == (5, 5)
-                        {
+                        if restricted.contains(&vertex) {
                             text("⌘")
                         } else {
                             text(" ")
@@ -497,8 +508,8 @@ impl<'a> Client {
         }
 
         let mut column = column![text(" ").size(letter_size)].spacing(spacing);
-        for i in 0..11 {
-            let i = 11 - i;
+        for i in 0..size {
+            let i = size - i;
             column = column.push(text!("{i:2}").size(letter_size).align_y(Vertical::Center));
         }
 
@@ -511,59 +522,71 @@ impl<'a> Client {
         let mut attacker_rating = String::new();
         let mut defender_rating = String::new();
 
-        let (game_id, attacker, attacker_time, defender, defender_time, board, play, status, texts) =
-            if let Some(game_handle) = &self.archived_game_handle {
-                attacker_rating = game_handle.game.attacker_rating.to_string_rounded();
-                defender_rating = game_handle.game.defender_rating.to_string_rounded();
-
-                let status = if game_handle.play < game_handle.game.plays.len() - 1 {
-                    &Status::Ongoing
-                } else {
-                    &game_handle.game.status
-                };
-
-                (
-                    &game_handle.game.id,
-                    &game_handle.game.attacker,
-                    game_handle.game.plays[game_handle.play]
-                        .attacker_time
-                        .fmt_shorthand(),
-                    &game_handle.game.defender,
-                    game_handle.game.plays[game_handle.play]
-                        .defender_time
-                        .fmt_shorthand(),
-                    &game_handle.boards[game_handle.play],
-                    game_handle.play,
-                    status,
-                    &game_handle.game.texts,
-                )
+        let (
+            game_id,
+            attacker,
+            attacker_time,
+            defender,
+            defender_time,
+            board,
+            play,
+            status,
+            game_end,
+            texts,
+        ) = if let Some(game_handle) = &self.archived_game_handle {
+            attacker_rating = game_handle.game.attacker_rating.to_string_rounded();
+            defender_rating = game_handle.game.defender_rating.to_string_rounded();
+
+            let (status, game_end) = if game_handle.play < game_handle.game.plays.len() - 1 {
+                (&Status::Ongoing, None)
             } else {
-                for user in self.users.values() {
-                    if self.attacker == user.name {
-                        attacker_rating = user.rating.to_string_rounded();
-                    }
-                    if self.defender == user.name {
-                        defender_rating = user.rating.to_string_rounded();
-                    }
-                }
+                (&game_handle.game.status, game_handle.game.game_end)
+            };
 
-                let Some(game) = &self.game else {
-                    panic!("we should be in a game");
-                };
+            (
+                &game_handle.game.id,
+                &game_handle.game.attacker,
+                game_handle.game.plays[game_handle.play]
+                    .attacker_time
+                    .fmt_shorthand(),
+                &game_handle.game.defender,
+                game_handle.game.plays[game_handle.play]
+                    .defender_time
+                    .fmt_shorthand(),
+                &game_handle.boards[game_handle.play],
+                game_handle.play,
+                status,
+                game_end,
+                &game_handle.game.texts,
+            )
+        } else {
+            for user in self.users.values() {
+                if self.attacker == user.name {
+                    attacker_rating = user.rating.to_string_rounded();
+                }
+                if self.defender == user.name {
+                    defender_rating = user.rating.to_string_rounded();
+                }
+            }
 
-                (
-                    &self.game_id,
-                    &self.attacker,
-                    self.time_attacker.fmt_shorthand(),
-                    &self.defender,
-                    self.time_defender.fmt_shorthand(),
-                    &game.board,
-                    game.previous_boards.0.len(),
-                    &self.status,
-                    &self.texts_game,
-                )
+            let Some(game) = &self.game else {
+                panic!("we should be in a game");
             };
 
+            (
+                &self.game_id,
+                &self.attacker,
+                self.time_attacker.fmt_shorthand(),
+                &self.defender,
+                self.time_defender.fmt_shorthand(),
+                &game.board,
+                game.previous_boards.0.len(),
+                &self.status,
+                self.game_end,
+                &self.texts_game,
+            )
+        };
+
         for user in self.users.values() {
             if self.attacker == user.name {
                 attacker_rating = user.rating.to_string_rounded();
@@ -725,6 +748,11 @@ impl<'a> Client {
             }
         }
 
+        if let Some(game_end) = game_end {
+            user_area =
+                user_area.push(text(game_end_text(game_end)).shaping(text::Shaping::Advanced));
+        }
+
         let spectator =
             column![text!("👥 ({})", self.spectators.len()).shaping(text::Shaping::Advanced)];
 
@@ -1169,6 +1197,7 @@ impl<'a> Client {
                                 if let Some("accept") = text.next() {
                                     self.my_turn = false;
                                     self.status = Status::Draw;
+                                    self.game_end = Some(GameEnd::Agreement);
 
                                     if let Some(game) = &mut self.game {
                                         game.turn = Role::Roleless;
@@ -1204,9 +1233,13 @@ impl<'a> Client {
                                 text.next();
                                 match text.next() {
                                     Some("attacker_wins") => self.status = Status::AttackerWins,
+                                    Some("draw") => self.status = Status::Draw,
                                     Some("defender_wins") => self.status = Status::DefenderWins,
                                     _ => {}
                                 }
+                                self.game_end = text
+                                    .next()
+                                    .and_then(|game_end| GameEnd::from_str(game_end).ok());
 
                                 if !self.sound_muted {
                                     thread::spawn(move || {
@@ -1227,6 +1260,7 @@ impl<'a> Client {
                             Some("join_game" | "resume_game" | "watch_game") => {
                                 self.screen = Screen::Game;
                                 self.status = Status::Ongoing;
+                                self.game_end = None;
                                 self.captures = HashSet::new();
                                 self.play_from = None;
                                 self.play_from_previous = None;
@@ -1410,9 +1444,15 @@ impl<'a> Client {
                             }
 
                             let to = text.next().expect("this should be to");
+                            let size = self
+                                .game
+                                .as_ref()
+                                .expect("you should have a game by now")
+                                .board
+                                .size;
 
                             if let (Ok(from), Ok(to)) =
-                                (Vertex::from_str(from), Vertex::from_str(to))
+                                (Vertex::parse(from, size), Vertex::parse(to, size))
                             {
                                 self.play_from_previous = Some(from);
                                 self.play_to_previous = Some(to);
@@ -1715,13 +1755,14 @@ impl<'a> Client {
         self.captures = HashSet::new();
 
         let game = self.game.as_mut().expect("you should have a game by now");
+        let size = game.board.size;
 
         match role {
             Some(role) => match game.read_line(&format!("play {role} {from} {to}\n")) {
                 Ok(vertexes) => {
                     if let Some(vertexes) = vertexes {
                         for vertex in vertexes.split_ascii_whitespace() {
-                            let Ok(vertex) = Vertex::from_str(vertex) else {
+                            let Ok(vertex) = Vertex::parse(vertex, size) else {
                                 panic!("this should be a valid vertex");
                             };
                             self.captures.insert(vertex);
@@ -1737,7 +1778,7 @@ impl<'a> Client {
                 Ok(vertexes) => {
                     if let Some(vertexes) = vertexes {
                         for vertex in vertexes.split_ascii_whitespace() {
-                            let Ok(vertex) = Vertex::from_str(vertex) else {
+                            let Ok(vertex) = Vertex::parse(vertex, size) else {
                                 panic!("this should be a valid vertex");
                             };
                             self.captures.insert(vertex);
@@ -2645,6 +2686,25 @@ fn pass_messages() -> impl Stream<Item = Message> {
     )
 }
 
+fn game_end_text(game_end: GameEnd) -> String {
+    let text = match game_end {
+        GameEnd::Escape => t!("The king escaped."),
+        GameEnd::ExitFort => t!("The king built an exit fort."),
+        GameEnd::KingCaptured => t!("The king was captured."),
+        GameEnd::Encirclement => t!("The defenders were surrounded."),
+        GameEnd::AllCaptured => t!("All of the attackers were captured."),
+        GameEnd::NoLegalMoves => t!("There were no legal moves."),
+        GameEnd::Timeout => t!("Time ran out."),
+        GameEnd::Resignation => t!("A player resigned."),
+        GameEnd::Repetition => t!("A position was repeated too many times."),
+        GameEnd::MoveLimit => t!("There were too many moves without a capture."),
+        GameEnd::InsufficientMaterial => t!("Too few attackers are left to capture the king."),
+        GameEnd::Agreement => t!("The players agreed to a draw."),
+    };
+
+    text.to_string()
+}
+
 fn handle_error<T, E: fmt::Display>(result: Result<T, E>) -> T {
     match result {
         Ok(value) => value,
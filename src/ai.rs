@@ -1,19 +1,86 @@
-use std::cmp::{max, min};
+use std::{
+    cmp::{max, min},
+    f64::consts::SQRT_2,
+    fmt,
+    io::{BufRead, BufReader, Write},
+    mem,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
 
+use anyhow::Context;
 use chrono::Utc;
-use rand::{RngCore, rngs::OsRng};
+use rand::{
+    Rng, RngCore, SeedableRng,
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    seq::SliceRandom,
+};
 
 use crate::{
-    board::Board,
-    game::Game,
-    play::{Plae, Play, Vertex},
+    evaluation::Evaluator,
+    game::{Game, TimeUnix, Undo},
+    opening_book::OpeningBook,
+    play::Plae,
+    play_ordering::PlayOrdering,
+    position::Position,
     role::Role,
-    space::Space,
+    solver,
     status::Status,
+    time::TimeSettings,
+    transposition_table::{Bound, Entry, TranspositionTable},
+    zobrist::ZOBRIST,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// `std::time::Instant` panics in the browser, so the AIs keep time by the wall clock there.
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct Instant(i64);
+
+#[cfg(target_arch = "wasm32")]
+impl Instant {
+    fn now() -> Self {
+        Self(Utc::now().timestamp_micros())
+    }
+
+    fn elapsed(&self) -> Duration {
+        Duration::from_micros(u64::try_from(Self::now().0 - self.0).unwrap_or_default())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl std::ops::Add<Duration> for Instant {
+    type Output = Self;
+
+    fn add(self, duration: Duration) -> Self {
+        let micros = i64::try_from(duration.as_micros()).unwrap_or(i64::MAX);
+        Self(self.0.saturating_add(micros))
+    }
+}
+
 pub trait AI {
     fn generate_move(&mut self, game: &Game) -> Option<Plae>;
+
+    /// The reply the AI expects to the play it chose last.
+    fn expected_reply(&self) -> Option<Plae> {
+        None
+    }
+
+    /// Searches `game` on the opponent's time until `stop` is set or the search is done, and
+    /// returns the best play found. The AIs that don't ponder return `None` at once.
+    fn ponder(&mut self, _game: &Game, _stop: &AtomicBool) -> Option<Plae> {
+        None
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -24,27 +91,8 @@ impl AI for AiBanal {
         if game.status != Status::Ongoing {
             return None;
         }
-        let mut game_clone = game.clone();
-
-        for x_from in 0..11 {
-            for y_from in 0..11 {
-                for x_to in 0..11 {
-                    for y_to in 0..11 {
-                        let play = Plae::Play(Play {
-                            role: game.turn,
-                            from: Vertex {
-                                x: x_from,
-                                y: y_from,
-                            },
-                            to: Vertex { x: x_to, y: y_to },
-                        });
-
-                        if game_clone.play(&play).is_ok() {
-                            return Some(play);
-                        }
-                    }
-                }
-            }
+        if let Some(play) = game.legal_moves().next() {
+            return Some(Plae::Play(play));
         }
 
         match game.turn {
@@ -55,68 +103,962 @@ impl AI for AiBanal {
     }
 }
 
+/// The AIs `AiSettings` can build.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AiKind {
+    /// `AiBanal`
+    #[default]
+    Banal,
+    /// `AiBasic`
+    Basic,
+    /// `AiMcts`
+    Mcts,
+}
+
+impl AiKind {
+    /// The options the AI takes. `weights` and `ponder` are set by the programs, not as
+    /// `KEY=VALUE`.
+    #[must_use]
+    pub fn options(&self) -> &'static [&'static str] {
+        match self {
+            Self::Banal => &[],
+            Self::Basic => &[
+                "depth", "time", "skill", "threads", "hash", "seed", "weights", "ponder",
+            ],
+            Self::Mcts => &["iterations", "time", "seed", "weights"],
+        }
+    }
+}
+
+impl fmt::Display for AiKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Banal => write!(f, "banal"),
+            Self::Basic => write!(f, "basic"),
+            Self::Mcts => write!(f, "mcts"),
+        }
+    }
+}
+
+impl FromStr for AiKind {
+    type Err = anyhow::Error;
+
+    fn from_str(kind: &str) -> anyhow::Result<Self> {
+        match kind {
+            "banal" => Ok(Self::Banal),
+            "basic" => Ok(Self::Basic),
+            "mcts" => Ok(Self::Mcts),
+            _ => Err(anyhow::Error::msg(format!(
+                "expected banal, basic, or mcts, got {kind}"
+            ))),
+        }
+    }
+}
+
+/// Which AI to build and how, written as `NAME [KEY=VALUE ...]`, for example
+/// `basic depth=4 time=5 seed=1`. An option that isn't set keeps the AI's default.
+///
+/// `basic` takes `depth`, `time` (the most seconds to spend on a move), `skill` (from 1 to
+/// `MAX_SKILL`), `threads`, `hash` (the size of the transposition table in megabytes), and
+/// `seed`. `mcts` takes `iterations`, `time`, and `seed`. `banal` takes none.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AiSettings {
+    pub kind: AiKind,
+    pub depth: Option<u64>,
+    pub time_to_move: Option<i64>,
+    pub skill: Option<u8>,
+    pub threads: Option<usize>,
+    pub hash: Option<usize>,
+    pub iterations: Option<u64>,
+    /// Seeds the random number generators, so the AI and its book make the same plays every time.
+    pub seed: Option<u64>,
+    /// The weights of the evaluation, for `basic` and `mcts`.
+    pub evaluator: Option<Evaluator>,
+    /// A book to play from before the AI searches, for any AI.
+    pub book: Option<Arc<OpeningBook>>,
+    /// Think on the opponent's time, for `basic`.
+    pub ponder: bool,
+}
+
+impl AiSettings {
+    /// The same settings for another `kind` of AI, leaving out the options it doesn't take.
+    #[must_use]
+    pub fn with_kind(&self, kind: AiKind) -> Self {
+        let takes = |option| kind.options().contains(&option);
+
+        Self {
+            kind,
+            depth: self.depth.filter(|_| takes("depth")),
+            time_to_move: self.time_to_move.filter(|_| takes("time")),
+            skill: self.skill.filter(|_| takes("skill")),
+            threads: self.threads.filter(|_| takes("threads")),
+            hash: self.hash.filter(|_| takes("hash")),
+            iterations: self.iterations.filter(|_| takes("iterations")),
+            seed: self.seed.filter(|_| takes("seed")),
+            evaluator: self.evaluator.clone().filter(|_| takes("weights")),
+            book: self.book.clone(),
+            ponder: self.ponder && takes("ponder"),
+        }
+    }
+
+    /// Checks that every option set is one the AI takes.
+    ///
+    /// # Errors
+    ///
+    /// If an option is set that the AI doesn't take.
+    pub fn check(&self) -> anyhow::Result<()> {
+        for (option, set) in [
+            ("depth", self.depth.is_some()),
+            ("time", self.time_to_move.is_some()),
+            ("skill", self.skill.is_some()),
+            ("threads", self.threads.is_some()),
+            ("hash", self.hash.is_some()),
+            ("iterations", self.iterations.is_some()),
+            ("seed", self.seed.is_some()),
+            ("weights", self.evaluator.is_some()),
+            ("ponder", self.ponder),
+        ] {
+            if set && !self.kind.options().contains(&option) {
+                return Err(anyhow::Error::msg(format!(
+                    "{} has no option {option}",
+                    self.kind
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A fresh AI, the basic AI sends what it finds at each depth to `info`.
+    #[must_use]
+    pub fn build(&self, info: Option<&Sender<SearchInfo>>) -> Box<dyn AI + Send> {
+        let mut ai = self.build_search(info);
+
+        if let Some(book) = &self.book {
+            let mut book = AiBook::new(OpeningBook::clone(book), ai);
+            if let Some(seed) = self.seed {
+                book.rng = StdRng::seed_from_u64(seed);
+            }
+            ai = Box::new(book);
+        }
+        if self.ponder {
+            let mut ponder = AiPonder::new(ai);
+            if let Some(time_to_move) = self.time_to_move {
+                ponder.time_to_move = time_to_move;
+            }
+            ai = Box::new(ponder);
+        }
+
+        ai
+    }
+
+    fn build_search(&self, info: Option<&Sender<SearchInfo>>) -> Box<dyn AI + Send> {
+        match self.kind {
+            AiKind::Banal => Box::new(AiBanal),
+            AiKind::Basic => Box::new(self.build_basic(info)),
+            AiKind::Mcts => {
+                let mut ai = AiMcts::default();
+                if let Some(iterations) = self.iterations {
+                    ai.iterations = iterations;
+                }
+                if let Some(time_to_move) = self.time_to_move {
+                    ai.time_to_move = time_to_move;
+                }
+                if let Some(seed) = self.seed {
+                    ai.rng = StdRng::seed_from_u64(seed);
+                }
+                if let Some(evaluator) = &self.evaluator {
+                    ai.evaluator = evaluator.clone();
+                }
+
+                Box::new(ai)
+            }
+        }
+    }
+
+    /// The basic AI with these settings, whatever the kind, the analysis uses it.
+    #[must_use]
+    pub fn build_basic(&self, info: Option<&Sender<SearchInfo>>) -> AiBasic {
+        let mut ai = AiBasic {
+            info: info.cloned(),
+            ..self
+                .skill
+                .map_or_else(AiBasic::default, AiBasic::with_skill)
+        };
+        if let Some(depth) = self.depth {
+            ai.depth = depth;
+        }
+        if let Some(time_to_move) = self.time_to_move {
+            ai.time_to_move = time_to_move;
+        }
+        if let Some(threads) = self.threads {
+            ai.threads = threads;
+        }
+        if let Some(hash) = self.hash {
+            ai.transposition_table = TranspositionTable::new(hash);
+        }
+        if let Some(seed) = self.seed {
+            ai.rng = StdRng::seed_from_u64(seed);
+        }
+        if let Some(evaluator) = &self.evaluator {
+            ai.evaluator = evaluator.clone();
+        }
+
+        ai
+    }
+}
+
+impl fmt::Display for AiSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+
+        if let Some(depth) = self.depth {
+            write!(f, " depth={depth}")?;
+        }
+        if let Some(time_to_move) = self.time_to_move {
+            write!(f, " time={time_to_move}")?;
+        }
+        if let Some(skill) = self.skill {
+            write!(f, " skill={skill}")?;
+        }
+        if let Some(threads) = self.threads {
+            write!(f, " threads={threads}")?;
+        }
+        if let Some(hash) = self.hash {
+            write!(f, " hash={hash}")?;
+        }
+        if let Some(iterations) = self.iterations {
+            write!(f, " iterations={iterations}")?;
+        }
+        if let Some(seed) = self.seed {
+            write!(f, " seed={seed}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for AiSettings {
+    type Err = anyhow::Error;
+
+    fn from_str(settings: &str) -> anyhow::Result<Self> {
+        let mut args = settings.split_ascii_whitespace();
+        let kind = AiKind::from_str(args.next().context("expected: NAME [KEY=VALUE ...]")?)?;
+        let mut ai_settings = Self {
+            kind,
+            ..Self::default()
+        };
+
+        for option in args {
+            let (key, value) = option
+                .split_once('=')
+                .with_context(|| format!("expected KEY=VALUE, got {option}"))?;
+            if !kind.options().contains(&key) {
+                return Err(anyhow::Error::msg(format!("{kind} has no option {key}")));
+            }
+
+            let err_msg = || format!("{key} can't be {value}");
+            match key {
+                "depth" => ai_settings.depth = Some(value.parse().with_context(err_msg)?),
+                "time" => ai_settings.time_to_move = Some(value.parse().with_context(err_msg)?),
+                "skill" => {
+                    let skill = value.parse().with_context(err_msg)?;
+                    if !(1..=MAX_SKILL).contains(&skill) {
+                        return Err(anyhow::Error::msg(format!(
+                            "the skill has to be from 1 to {MAX_SKILL}"
+                        )));
+                    }
+                    ai_settings.skill = Some(skill);
+                }
+                "threads" => ai_settings.threads = Some(value.parse().with_context(err_msg)?),
+                "hash" => ai_settings.hash = Some(value.parse().with_context(err_msg)?),
+                "iterations" => {
+                    ai_settings.iterations = Some(value.parse().with_context(err_msg)?);
+                }
+                "seed" => ai_settings.seed = Some(value.parse().with_context(err_msg)?),
+                _ => {
+                    return Err(anyhow::Error::msg(format!(
+                        "{key} can't be set as KEY=VALUE"
+                    )));
+                }
+            }
+        }
+
+        Ok(ai_settings)
+    }
+}
+
+/// Counts from a search, to measure how well the plays are ordered.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchStats {
+    /// The depth of the deepest search that finished, in plays.
+    pub depth: u64,
+    /// The positions searched at every depth.
+    pub nodes: u64,
+    /// The positions where a play was good enough to skip the rest.
+    pub cutoffs: u64,
+    /// The cutoffs made by the first play tried.
+    pub first_play_cutoffs: u64,
+    /// The positions settled by the transposition table.
+    pub table_hits: u64,
+}
+
+impl SearchStats {
+    /// The effective branching factor, how many times more positions each play deeper costs.
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn branching_factor(&self) -> f64 {
+        if self.depth == 0 {
+            return 0.0;
+        }
+
+        (self.nodes as f64).powf(1.0 / self.depth as f64)
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "depth {} nodes {} cutoffs {} first_play_cutoffs {} table_hits {} branching_factor {:.2}",
+            self.depth,
+            self.nodes,
+            self.cutoffs,
+            self.first_play_cutoffs,
+            self.table_hits,
+            self.branching_factor()
+        )
+    }
+}
+
+/// What the search found at one depth, like the `info` lines of UCI engines. It displays as:
+///
+/// `info depth DEPTH nodes NODES nps NODES_PER_SECOND time MILLISECONDS score SCORE pv LINE`
+///
+/// **SCORE** is `win`, `loss`, or how good the position is for the side to play, and **LINE**
+/// has the format `FROM-TO FROM-TO ...`, the best play and the replies the search expects.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchInfo {
+    pub depth: u64,
+    /// The positions the main thread searched so far.
+    pub nodes: u64,
+    /// How long the search has taken so far.
+    pub time: Duration,
+    /// The value for the side to play, `i32::MAX` for a win and `-i32::MAX` for a loss.
+    pub score: i32,
+    /// The principal variation.
+    pub pv: Vec<Plae>,
+}
+
+impl SearchInfo {
+    /// The positions searched per second.
+    #[must_use]
+    pub fn nodes_per_second(&self) -> u64 {
+        let milliseconds = u64::try_from(self.time.as_millis()).unwrap_or(u64::MAX);
+        self.nodes.saturating_mul(1_000) / milliseconds.max(1)
+    }
+}
+
+impl fmt::Display for SearchInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "info depth {} nodes {} nps {} time {} score ",
+            self.depth,
+            self.nodes,
+            self.nodes_per_second(),
+            self.time.as_millis(),
+        )?;
+
+        match self.score {
+            i32::MAX => write!(f, "win")?,
+            score if score == -i32::MAX => write!(f, "loss")?,
+            score => write!(f, "{score}")?,
+        }
+
+        write!(f, " pv")?;
+        for play in &self.pv {
+            match play {
+                Plae::Play(play) => write!(f, " {}-{}", play.from, play.to)?,
+                Plae::AttackerResigns | Plae::DefenderResigns => write!(f, " resigns")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// How many more moves the clock is split between.
+const MOVES_TO_GO: i64 = 30;
+
+/// How long a pondering search may go on, it is stopped long before.
+const PONDER_TIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The strongest skill `AiBasic::with_skill` takes, the weakest is 1.
+pub const MAX_SKILL: u8 = 20;
+
 #[derive(Clone, Debug)]
 pub struct AiBasic {
+    /// The deepest the search goes, in plays.
     pub depth: u64,
-    // seconds
+    /// The most time to spend on a move, in seconds.
     pub time_to_move: i64,
+    /// How many threads search at once, sharing the transposition table. With one thread the
+    /// same position always gets the same search.
+    pub threads: usize,
+    /// What was learned about positions already searched, kept from move to move.
+    pub transposition_table: TranspositionTable,
+    /// Which plays to search first.
+    pub play_ordering: PlayOrdering,
+    /// Counts from the last search, added up over the threads.
+    pub stats: SearchStats,
+    /// Scores the positions at the end of the search.
+    pub evaluator: Evaluator,
+    /// How many positions the solver looks at for a forced win before the search, 0 skips it.
+    pub solver_nodes: u64,
+    /// At most how much is added to or taken from the score of a position at the end of the
+    /// search, to play worse.
+    pub noise: u32,
+    /// If it isn't 0 the play is chosen at random by a softmax over the scores of the plays with
+    /// this temperature, so a play that scores close to the best is played almost as often.
+    pub temperature: f64,
+    /// Chooses the noise and the softmax plays.
+    pub rng: StdRng,
+    /// Where to send a `SearchInfo` each time the main thread finishes a depth.
+    pub info: Option<Sender<SearchInfo>>,
+    /// What the last search found at each depth it finished.
+    pub analysis: Vec<SearchInfo>,
 }
 
 impl Default for AiBasic {
     fn default() -> Self {
         Self {
-            depth: 4,
+            depth: 10,
             time_to_move: 15,
+            threads: 1,
+            transposition_table: TranspositionTable::default(),
+            play_ordering: PlayOrdering::default(),
+            stats: SearchStats::default(),
+            evaluator: Evaluator::default(),
+            solver_nodes: 10_000,
+            noise: 0,
+            temperature: 0.0,
+            rng: StdRng::from_entropy(),
+            info: None,
+            analysis: Vec::new(),
         }
     }
 }
 
 impl AI for AiBasic {
     fn generate_move(&mut self, game: &Game) -> Option<Plae> {
+        self.analysis.clear();
+        if game.status != Status::Ongoing {
+            return None;
+        }
+
+        if self.solver_nodes > 0 {
+            let win = solver::prove(game, game.turn, self.solver_nodes);
+            if let Some(play) = win.and_then(|line| line.into_iter().next()) {
+                return Some(play);
+            }
+        }
+
+        let mut root_values = (self.temperature > 0.0).then(Vec::new);
+        self.lazy_smp(
+            game,
+            self.time_budget(game),
+            &AtomicBool::new(false),
+            &mut root_values,
+        )
+    }
+
+    /// The second play of the principal variation of the last search.
+    fn expected_reply(&self) -> Option<Plae> {
+        self.analysis.last()?.pv.get(1).cloned()
+    }
+
+    fn ponder(&mut self, game: &Game, stop: &AtomicBool) -> Option<Plae> {
         if game.status != Status::Ongoing {
             return None;
         }
 
-        self.minimax_search(game)
+        let mut root_values = (self.temperature > 0.0).then(Vec::new);
+        self.lazy_smp(game, PONDER_TIME, stop, &mut root_values)
     }
 }
 
 impl AiBasic {
-    fn minimax_search(&mut self, game: &Game) -> Option<Plae> {
-        let cutoff_time = Utc::now().timestamp() + self.time_to_move;
-        let alpha = i32::MIN;
-        let beta = i32::MAX;
+    /// An AI that plays at `skill`, from 1 to `MAX_SKILL`, which plays as well as the default.
+    /// The lower the skill the shallower the search, the more noise, and the hotter the softmax.
+    #[must_use]
+    pub fn with_skill(skill: u8) -> Self {
+        let skill = skill.clamp(1, MAX_SKILL);
+        let weakness = MAX_SKILL - skill;
+
+        Self {
+            depth: u64::from(skill.div_ceil(2)),
+            solver_nodes: u64::from(skill) * 500,
+            noise: u32::from(weakness) * 10,
+            temperature: f64::from(weakness) * 10.0,
+            ..Self::default()
+        }
+    }
+
+    /// How long to think about the next move. With a clock it is a share of the time left plus
+    /// most of the increment, but never more than half of the time left or `time_to_move`.
+    #[must_use]
+    pub fn time_budget(&self, game: &Game) -> Duration {
+        time_budget(game, self.time_to_move)
+    }
+
+    /// Searches every play from the position with the full window, and returns them with their
+    /// values for the side to play, the best first. A value of `i32::MAX` is a win and
+    /// `-i32::MAX` a loss. The search goes to `depth` unless it runs out of time.
+    pub fn play_values(&mut self, game: &Game) -> Vec<(i32, Plae)> {
+        if game.status != Status::Ongoing {
+            return Vec::new();
+        }
 
-        let (value, play) = match game.turn {
-            Role::Attacker => self.min_value(game, alpha, beta, cutoff_time, 0),
-            Role::Defender => self.max_value(game, alpha, beta, cutoff_time, 0),
-            Role::Roleless => panic!("It is no ones turn!"),
+        let mut root_values = Some(Vec::new());
+        let play = self.lazy_smp(
+            game,
+            self.time_budget(game),
+            &AtomicBool::new(false),
+            &mut root_values,
+        );
+
+        let mut values: Vec<_> = root_values
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(value, play)| match game.turn {
+                Role::Attacker => (value.saturating_neg(), play),
+                Role::Defender | Role::Roleless => (value.max(-i32::MAX), play),
+            })
+            .collect();
+
+        // There was only one play, so nothing was searched.
+        if values.is_empty() {
+            values.extend(play.map(|play| (0, play)));
+        }
+
+        values.sort_by_key(|(value, _play)| -value);
+        values
+    }
+
+    /// Runs the search on every thread and returns the best play of the deepest search that
+    /// finished in time, or before `stop` was set. The helper threads start on different plays
+    /// and depths and fill the transposition table for the main thread, they stop when it does.
+    ///
+    /// If `root_values` is set the root plays are searched with the full window and it gets the
+    /// value of each of them.
+    fn lazy_smp(
+        &mut self,
+        game: &Game,
+        budget: Duration,
+        stop: &AtomicBool,
+        root_values: &mut Option<Vec<(i32, Plae)>>,
+    ) -> Option<Plae> {
+        let start = Instant::now();
+
+        self.stats = SearchStats::default();
+        self.analysis.clear();
+        let mut plays = game.all_legal_plays();
+        if plays.len() < 2 {
+            return plays.pop();
+        }
+
+        self.play_ordering.new_search(game);
+        self.play_ordering.order(game, &mut plays, None, 0);
+        let first_play = plays[0].clone();
+
+        let mut search = Search {
+            depth: self.depth,
+            start,
+            budget,
+            deadline: start + budget,
+            stop,
+            transposition_table: &self.transposition_table,
+            evaluator: &self.evaluator,
+            noise: self.noise,
+            noise_seed: self.rng.next_u64(),
+            play_ordering: mem::take(&mut self.play_ordering),
+            stats: SearchStats::default(),
+            root_values: root_values.take(),
+            info: self.info.as_ref(),
+            analysis: Some(Vec::new()),
         };
 
-        println!("value: {value}");
-        play
+        let results = thread::scope(|scope| {
+            let helpers: Vec<_> = (1..self.threads)
+                .map(|thread| {
+                    let mut helper = search.helper();
+                    let mut game = game.clone();
+                    let mut plays = plays.clone();
+                    let first = thread % plays.len();
+                    plays.rotate_left(first);
+
+                    scope.spawn(move || {
+                        helper.play_ordering.new_search(&game);
+                        let best =
+                            helper.iterative_deepening(&mut game, plays, 1 + thread as u64 % 2);
+                        (best, helper.stats)
+                    })
+                })
+                .collect();
+
+            let mut results = vec![(
+                search.iterative_deepening(&mut game.clone(), plays, 1),
+                search.stats.clone(),
+            )];
+            stop.store(true, Ordering::Relaxed);
+
+            for helper in helpers {
+                if let Ok(result) = helper.join() {
+                    results.push(result);
+                }
+            }
+
+            results
+        });
+        self.play_ordering = search.play_ordering;
+        *root_values = search.root_values;
+        self.analysis = search.analysis.unwrap_or_default();
+
+        let mut best: Option<(u64, i32, Plae)> = None;
+        for (result, stats) in results {
+            self.stats.nodes += stats.nodes;
+            self.stats.cutoffs += stats.cutoffs;
+            self.stats.first_play_cutoffs += stats.first_play_cutoffs;
+            self.stats.table_hits += stats.table_hits;
+
+            // The main thread comes first and wins ties.
+            if let Some(result) = result {
+                if best.as_ref().is_none_or(|best| result.0 > best.0) {
+                    best = Some(result);
+                }
+            }
+        }
+
+        let Some((depth, _value, play)) = best else {
+            return Some(first_play);
+        };
+        self.stats.depth = depth;
+
+        if self.temperature > 0.0 {
+            if let Some(root_values) = root_values {
+                if let Some(play) = self.softmax(game.turn, root_values) {
+                    return Some(play);
+                }
+            }
+        }
+
+        Some(play)
     }
 
-    fn max_value(
+    /// Chooses one of the root plays at random, the better its value for `turn` the likelier.
+    fn softmax(&mut self, turn: Role, root_values: &[(i32, Plae)]) -> Option<Plae> {
+        let scores: Vec<_> = root_values
+            .iter()
+            .map(|(value, _play)| match turn {
+                Role::Attacker => -f64::from(*value),
+                Role::Defender | Role::Roleless => f64::from(*value),
+            })
+            .collect();
+
+        let best = scores.iter().copied().fold(f64::MIN, f64::max);
+        let weights = scores
+            .iter()
+            .map(|score| ((score - best) / self.temperature).exp());
+        let index = WeightedIndex::new(weights).ok()?.sample(&mut self.rng);
+
+        Some(root_values[index].1.clone())
+    }
+}
+
+/// The state of one thread of the `AiBasic` search.
+struct Search<'a> {
+    depth: u64,
+    start: Instant,
+    budget: Duration,
+    deadline: Instant,
+    /// Set when the main thread is done.
+    stop: &'a AtomicBool,
+    transposition_table: &'a TranspositionTable,
+    evaluator: &'a Evaluator,
+    noise: u32,
+    /// Picks the noise of each position, it changes from move to move.
+    noise_seed: u64,
+    play_ordering: PlayOrdering,
+    stats: SearchStats,
+    /// The value of every root play from the deepest search that finished, kept for a softmax.
+    /// The root plays are searched with the full window then, so every value is exact.
+    root_values: Option<Vec<(i32, Plae)>>,
+    /// Where the main thread sends what it found at each depth.
+    info: Option<&'a Sender<SearchInfo>>,
+    /// What the main thread found at each depth.
+    analysis: Option<Vec<SearchInfo>>,
+}
+
+impl<'a> Search<'a> {
+    /// A search for another thread, that shares the table and deadline.
+    fn helper(&self) -> Search<'a> {
+        Search {
+            play_ordering: PlayOrdering::default(),
+            stats: SearchStats::default(),
+            root_values: None,
+            info: None,
+            analysis: None,
+            ..*self
+        }
+    }
+
+    /// Searches one play deeper at a time from `first_depth` and returns the depth, value, and
+    /// best play of the deepest search that finished in time.
+    fn iterative_deepening(
         &mut self,
+        game: &mut Game,
+        mut plays: Vec<Plae>,
+        first_depth: u64,
+    ) -> Option<(u64, i32, Plae)> {
+        let mut best = None;
+
+        for depth in first_depth..=self.depth {
+            let Some((value, play)) = self.search_root(game, &plays, depth) else {
+                break;
+            };
+
+            // Search the best play first next time.
+            if let Some(index) = plays.iter().position(|play_2| *play_2 == play) {
+                plays[..=index].rotate_right(1);
+            }
+            if self.analysis.is_some() {
+                self.report(game, depth, value, &play);
+            }
+            best = Some((depth, value, play));
+
+            if value == i32::MIN || value == i32::MAX || self.start.elapsed() > self.budget / 2 {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// Records and sends what the search found at `depth`.
+    fn report(&mut self, game: &mut Game, depth: u64, value: i32, play: &Plae) {
+        let score = value.max(-i32::MAX);
+        let info = SearchInfo {
+            depth,
+            nodes: self.stats.nodes,
+            time: self.start.elapsed(),
+            score: match game.turn {
+                Role::Attacker => -score,
+                Role::Defender | Role::Roleless => score,
+            },
+            pv: self.principal_variation(game, play, depth),
+        };
+
+        if let Some(sender) = self.info {
+            // No one listening isn't a reason to stop searching.
+            let _ = sender.send(info.clone());
+        }
+        if let Some(analysis) = &mut self.analysis {
+            analysis.push(info);
+        }
+    }
+
+    /// The best play followed by the best plays stored in the transposition table, up to
+    /// `depth` plays.
+    fn principal_variation(&self, game: &mut Game, play: &Plae, depth: u64) -> Vec<Plae> {
+        let mut pv = Vec::new();
+        let mut undos = Vec::new();
+        let mut play = Some(play.clone());
+
+        while let Some(play_1) = play {
+            if pv.len() as u64 >= depth {
+                break;
+            }
+            let Ok((_captures, undo)) = game.make_untimed(&play_1) else {
+                break;
+            };
+
+            undos.push(undo);
+            pv.push(play_1);
+            play = self
+                .transposition_table
+                .get(ZOBRIST.hash(&game.board, game.turn))
+                .and_then(|entry| entry.play);
+        }
+
+        for undo in undos.into_iter().rev() {
+            game.unmake(undo);
+        }
+
+        pv
+    }
+
+    /// Returns `None` if the deadline passes before the search finishes.
+    fn search_root(&mut self, game: &mut Game, plays: &[Plae], depth: u64) -> Option<(i32, Plae)> {
+        let mut alpha = i32::MIN;
+        let mut beta = i32::MAX;
+        let mut best: Option<(i32, Plae)> = None;
+        let mut values = Vec::new();
+        self.stats.nodes += 1;
+
+        for play in plays {
+            let Ok((_captures, undo)) = game.make_untimed(play) else {
+                continue;
+            };
+            let result = match game.turn {
+                Role::Attacker => self.min_value(game, alpha, beta, depth - 1, 1),
+                Role::Defender | Role::Roleless => self.max_value(game, alpha, beta, depth - 1, 1),
+            };
+            game.unmake(undo);
+            let (value, _play) = result?;
+            values.push((value, play.clone()));
+
+            let better = match game.turn {
+                Role::Attacker => best.as_ref().is_none_or(|(best, _)| value < *best),
+                Role::Defender => best.as_ref().is_none_or(|(best, _)| value > *best),
+                Role::Roleless => false,
+            };
+            if better {
+                best = Some((value, play.clone()));
+
+                if self.root_values.is_none() {
+                    match game.turn {
+                        Role::Attacker => beta = min(beta, value),
+                        Role::Defender => alpha = max(alpha, value),
+                        Role::Roleless => {}
+                    }
+                }
+            }
+        }
+
+        if let Some(root_values) = &mut self.root_values {
+            *root_values = values;
+        }
+
+        best
+    }
+
+    /// The score of the position, give or take the noise if the game isn't over.
+    fn evaluate(&self, game: &Game) -> i32 {
+        let value = self.evaluator.evaluate(game);
+        if self.noise == 0 || game.status != Status::Ongoing {
+            return value;
+        }
+
+        let key = ZOBRIST.hash(&game.board, game.turn) ^ self.noise_seed;
+        let noise = key % (u64::from(self.noise) * 2 + 1);
+        let value =
+            i64::from(value) + i64::try_from(noise).unwrap_or_default() - i64::from(self.noise);
+
+        // The noise never makes a game look won.
+        i32::try_from(value.clamp(i64::from(i32::MIN + 1), i64::from(i32::MAX - 1)))
+            .unwrap_or_default()
+    }
+
+    /// The value stored for the position if it settles the search, and the best play stored
+    /// for it to try first otherwise.
+    fn probe(
+        &self,
         game: &Game,
-        mut alpha: i32,
+        alpha: i32,
         beta: i32,
-        cutoff_time: i64,
         depth: u64,
-    ) -> (i32, Option<Plae>) {
-        if Utc::now().timestamp() > cutoff_time
-            || depth > self.depth
-            || game.status != Status::Ongoing
-        {
-            return (game.utility(), None);
+    ) -> (Option<(i32, Option<Plae>)>, Option<Plae>) {
+        let key = ZOBRIST.hash(&game.board, game.turn);
+        let Some(entry) = self.transposition_table.get(key) else {
+            return (None, None);
+        };
+
+        if entry.depth >= depth {
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.value >= beta,
+                Bound::Upper => entry.value <= alpha,
+            };
+
+            if cutoff {
+                return (Some((entry.value, entry.play)), None);
+            }
+        }
+
+        (None, entry.play)
+    }
+
+    fn store(
+        &self,
+        game: &Game,
+        alpha: i32,
+        beta: i32,
+        depth: u64,
+        value: i32,
+        play: Option<Plae>,
+    ) {
+        let bound = if value >= beta {
+            Bound::Lower
+        } else if value <= alpha {
+            Bound::Upper
+        } else {
+            Bound::Exact
+        };
+
+        self.transposition_table.insert(&Entry {
+            key: ZOBRIST.hash(&game.board, game.turn),
+            depth,
+            bound,
+            value,
+            play,
+        });
+    }
+
+    /// The legal plays, the best first.
+    fn ordered_plays(&self, game: &Game, table_play: Option<&Plae>, from_root: usize) -> Vec<Plae> {
+        let mut plays = game.all_legal_plays();
+        self.play_ordering
+            .order(game, &mut plays, table_play, from_root);
+        plays
+    }
+
+    fn max_value(
+        &mut self,
+        game: &mut Game,
+        alpha: i32,
+        beta: i32,
+        depth: u64,
+        from_root: usize,
+    ) -> Option<(i32, Option<Plae>)> {
+        if Instant::now() > self.deadline || self.stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        self.stats.nodes += 1;
+        if depth == 0 || game.status != Status::Ongoing {
+            return Some((self.evaluate(game), None));
+        }
+
+        let (stored, table_play) = self.probe(game, alpha, beta, depth);
+        if stored.is_some() {
+            self.stats.table_hits += 1;
+            return stored;
         }
 
+        let alpha_original = alpha;
+        let mut alpha = alpha;
         let (mut value, mut play_1) = (i32::MIN, None);
-        for play_2 in game.all_legal_plays() {
-            let mut game = game.clone();
-            game.play(&play_2).unwrap();
-            let (value_new, _play) = self.min_value(&game, alpha, beta, cutoff_time, depth + 1);
+        for (index, play_2) in self
+            .ordered_plays(game, table_play.as_ref(), from_root)
+            .into_iter()
+            .enumerate()
+        {
+            let Ok((_captures, undo)) = game.make_untimed(&play_2) else {
+                continue;
+            };
+            let result = self.min_value(game, alpha, beta, depth - 1, from_root + 1);
+            game.unmake(undo);
+            let (value_new, _play) = result?;
 
             if value_new > value {
                 (value, play_1) = (value_new, Some(play_2));
@@ -124,87 +1066,662 @@ impl AiBasic {
             }
 
             if value >= beta {
-                return (value, play_1);
+                self.cutoff(game, play_1.as_ref(), index, from_root, depth);
+                break;
             }
         }
 
-        (value, play_1)
+        self.store(game, alpha_original, beta, depth, value, play_1.clone());
+        Some((value, play_1))
     }
 
     fn min_value(
         &mut self,
-        game: &Game,
+        game: &mut Game,
         alpha: i32,
-        mut beta: i32,
-        cutoff_time: i64,
+        beta: i32,
         depth: u64,
-    ) -> (i32, Option<Plae>) {
-        if Utc::now().timestamp() > cutoff_time
-            || depth > self.depth
-            || game.status != Status::Ongoing
-        {
-            return (game.utility(), None);
+        from_root: usize,
+    ) -> Option<(i32, Option<Plae>)> {
+        if Instant::now() > self.deadline || self.stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        self.stats.nodes += 1;
+        if depth == 0 || game.status != Status::Ongoing {
+            return Some((self.evaluate(game), None));
         }
 
+        let (stored, table_play) = self.probe(game, alpha, beta, depth);
+        if stored.is_some() {
+            self.stats.table_hits += 1;
+            return stored;
+        }
+
+        let beta_original = beta;
+        let mut beta = beta;
         let (mut value, mut play_1) = (i32::MAX, None);
-        for play_2 in game.all_legal_plays() {
-            let mut game = game.clone();
-            game.play(&play_2).unwrap();
-            let (value_new, _play) = self.max_value(&game, alpha, beta, cutoff_time, depth + 1);
+        for (index, play_2) in self
+            .ordered_plays(game, table_play.as_ref(), from_root)
+            .into_iter()
+            .enumerate()
+        {
+            let Ok((_captures, undo)) = game.make_untimed(&play_2) else {
+                continue;
+            };
+            let result = self.max_value(game, alpha, beta, depth - 1, from_root + 1);
+            game.unmake(undo);
+            let (value_new, _play) = result?;
 
             if value_new < value {
                 (value, play_1) = (value_new, Some(play_2));
                 beta = min(beta, value);
             }
             if value <= alpha {
-                return (value, play_1);
+                self.cutoff(game, play_1.as_ref(), index, from_root, depth);
+                break;
             }
         }
 
-        (value, play_1)
+        self.store(game, alpha, beta_original, depth, value, play_1.clone());
+        Some((value, play_1))
+    }
+
+    /// Counts a cutoff made by the `index`th play tried and remembers the play that made it.
+    fn cutoff(
+        &mut self,
+        game: &Game,
+        play: Option<&Plae>,
+        index: usize,
+        from_root: usize,
+        depth: u64,
+    ) {
+        self.stats.cutoffs += 1;
+        if index == 0 {
+            self.stats.first_play_cutoffs += 1;
+        }
+
+        if let Some(play) = play {
+            self.play_ordering.cutoff(game, play, from_root, depth);
+        }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ZobristTable {
-    /// Bitstrings representing piece placement
-    piece_bits: [[u64; 3]; 11 * 11],
-    /// Bitstring to use used when it's the defender's move.
-    defender_to_move_bits: u64,
+/// Monte Carlo tree search: UCT picks which plays to look at, and each look ends with a playout
+/// of random plays, except that the king always escapes when it can.
+#[derive(Clone, Debug)]
+pub struct AiMcts {
+    /// The most playouts to run for a move.
+    pub iterations: u64,
+    /// The most time to spend on a move, in seconds.
+    pub time_to_move: i64,
+    /// The most plays in a playout before the position is scored as it stands.
+    pub playout_depth: u32,
+    /// How much UCT favors the plays that were tried the least.
+    pub exploration: f64,
+    /// Chooses the plays of the playouts, seed it to get the same plays every time.
+    pub rng: StdRng,
+    /// Scores the positions where a playout stops before the game ends.
+    pub evaluator: Evaluator,
 }
 
-impl Default for ZobristTable {
+impl Default for AiMcts {
     fn default() -> Self {
-        let mut rng = OsRng;
+        Self {
+            iterations: 100_000,
+            time_to_move: 15,
+            playout_depth: 100,
+            exploration: SQRT_2,
+            rng: StdRng::from_entropy(),
+            evaluator: Evaluator::default(),
+        }
+    }
+}
+
+impl AI for AiMcts {
+    fn generate_move(&mut self, game: &Game) -> Option<Plae> {
+        if game.status != Status::Ongoing {
+            return None;
+        }
+
+        let mut plays = game.all_legal_plays();
+        if plays.len() < 2 {
+            return plays.pop();
+        }
+
+        // The playouts can't tell a win from a play that keeps a win in hand.
+        let win = match game.turn {
+            Role::Attacker => Status::AttackerWins,
+            Role::Defender | Role::Roleless => Status::DefenderWins,
+        };
+        // Made and unmade along every path, cloning the game each time would cost more than the
+        // search.
+        let mut game_2 = game.clone();
+        game_2.undo_stack.clear();
+        for play in &plays {
+            if let Ok((_captures, undo)) = game_2.make_untimed(play) {
+                let wins = game_2.status == win;
+                game_2.unmake(undo);
+
+                if wins {
+                    return Some(play.clone());
+                }
+            }
+        }
+
+        let deadline = Instant::now() + time_budget(game, self.time_to_move);
+        let mut tree = vec![Node::new(None, None, game)];
+
+        let mut undos = Vec::new();
+
+        for _ in 0..self.iterations {
+            if Instant::now() > deadline {
+                break;
+            }
+
+            let leaf = self.select_and_expand(&mut tree, &mut game_2, &mut undos);
+            let defender_wins = self.playout(&mut game_2, &mut undos);
+
+            while let Some(undo) = undos.pop() {
+                game_2.unmake(undo);
+            }
+
+            let mut index = Some(leaf);
+            while let Some(node) = index {
+                tree[node].visits += 1;
+                tree[node].defender_wins += defender_wins;
+                index = tree[node].parent;
+            }
+        }
+
+        tree[0]
+            .children
+            .iter()
+            .max_by_key(|child| tree[**child].visits)
+            .and_then(|child| tree[*child].play.clone())
+    }
+}
+
+impl AiMcts {
+    /// Walks down the tree by UCT making the plays on `game` and adds a node for a play that
+    /// wasn't tried yet. The plays can be taken back with `undos`.
+    fn select_and_expand(
+        &mut self,
+        tree: &mut Vec<Node>,
+        game: &mut Game,
+        undos: &mut Vec<Undo>,
+    ) -> usize {
+        let mut index = 0;
 
-        let mut hashes = [[0; 3]; 121];
-        for hash in &mut hashes {
-            *hash = [rng.next_u64(), rng.next_u64(), rng.next_u64()];
+        loop {
+            if game.status != Status::Ongoing {
+                return index;
+            }
+
+            if !tree[index].untried.is_empty() {
+                let untried = &mut tree[index].untried;
+                let play = untried.swap_remove(self.rng.gen_range(0..untried.len()));
+                let Ok((_captures, undo)) = game.make_untimed(&play) else {
+                    return index;
+                };
+                undos.push(undo);
+
+                tree.push(Node::new(Some(index), Some(play), game));
+                let child = tree.len() - 1;
+                tree[index].children.push(child);
+                return child;
+            }
+
+            let Some(child) = self.best_child(tree, index) else {
+                return index;
+            };
+            if let Some(play) = &tree[child].play {
+                let Ok((_captures, undo)) = game.make_untimed(play) else {
+                    return index;
+                };
+                undos.push(undo);
+            }
+            index = child;
         }
+    }
+
+    /// The child with the highest upper confidence bound, for the side to play at `index`.
+    fn best_child(&self, tree: &[Node], index: usize) -> Option<usize> {
+        let node = &tree[index];
+        let log_visits = f64::from(node.visits).ln();
+
+        node.children.iter().copied().max_by(|a, b| {
+            let uct = |child: usize| {
+                let child = &tree[child];
+                let visits = f64::from(child.visits);
+                let wins = match node.turn {
+                    Role::Attacker => visits - child.defender_wins,
+                    Role::Defender | Role::Roleless => child.defender_wins,
+                };
+
+                wins / visits + self.exploration * (log_visits / visits).sqrt()
+            };
+
+            uct(*a).total_cmp(&uct(*b))
+        })
+    }
+
+    /// Plays random plays to the end of the game or `playout_depth`, and returns how much the
+    /// defender won: 1 for a win, 0 for a loss, and in between for a draw or an unfinished game.
+    fn playout(&mut self, game: &mut Game, undos: &mut Vec<Undo>) -> f64 {
+        for _ in 0..self.playout_depth {
+            if game.status != Status::Ongoing {
+                break;
+            }
+
+            let exits = game.rules.exits(game.board.size);
+            let plays: Vec<_> = game.legal_moves().collect();
+            let play = plays
+                .iter()
+                .find(|play| game.board.king.contains(&play.from) && exits.contains(&play.to))
+                .or_else(|| plays.choose(&mut self.rng));
+
+            let play = match play {
+                Some(play) => Plae::Play(play.clone()),
+                None => match game.turn {
+                    Role::Attacker => Plae::AttackerResigns,
+                    Role::Defender | Role::Roleless => Plae::DefenderResigns,
+                },
+            };
+            let Ok((_captures, undo)) = game.make_untimed(&play) else {
+                break;
+            };
+            undos.push(undo);
+        }
+
+        match game.status {
+            Status::AttackerWins => 0.0,
+            Status::Draw => 0.5,
+            Status::DefenderWins => 1.0,
+            Status::Ongoing => {
+                0.5 + (f64::from(self.evaluator.evaluate(game)) / 100.0).tanh() / 2.0
+            }
+        }
+    }
+}
 
+/// Plays from an `OpeningBook` while the position is in it, and lets another AI search after.
+pub struct AiBook {
+    pub book: OpeningBook,
+    /// The AI that plays when the book has nothing.
+    pub ai: Box<dyn AI + Send>,
+    /// Chooses between the book plays, seed it to get the same plays every time.
+    pub rng: StdRng,
+    /// Whether the last play came from the book.
+    from_book: bool,
+    /// The reply the book made most to its last play.
+    book_reply: Option<Plae>,
+}
+
+impl AiBook {
+    #[must_use]
+    pub fn new(book: OpeningBook, ai: Box<dyn AI + Send>) -> Self {
         Self {
-            piece_bits: hashes,
-            defender_to_move_bits: rng.next_u64(),
+            book,
+            ai,
+            rng: StdRng::from_entropy(),
+            from_book: false,
+            book_reply: None,
         }
     }
 }
 
-impl ZobristTable {
+impl AI for AiBook {
+    fn generate_move(&mut self, game: &Game) -> Option<Plae> {
+        let Some(play) = self.book.choose(game, &mut self.rng) else {
+            self.from_book = false;
+            return self.ai.generate_move(game);
+        };
+
+        let mut game = game.clone();
+        self.from_book = true;
+        self.book_reply = game
+            .play(&play)
+            .ok()
+            .and_then(|_captures| self.book.most_played(&game));
+
+        Some(play)
+    }
+
+    fn expected_reply(&self) -> Option<Plae> {
+        if self.from_book {
+            self.book_reply.clone()
+        } else {
+            self.ai.expected_reply()
+        }
+    }
+
+    fn ponder(&mut self, game: &Game, stop: &AtomicBool) -> Option<Plae> {
+        self.ai.ponder(game, stop)
+    }
+}
+
+/// Lets another AI think on the opponent's time. After each play it searches the position after
+/// the reply it expects on another thread. If the opponent makes that reply the search goes on
+/// for the time the move gets, otherwise it is stopped and thrown away.
+pub struct AiPonder {
+    /// The AI that plays, it is on the pondering thread while the opponent thinks.
+    ai: Option<Box<dyn AI + Send>>,
+    /// The most time to spend on a move after the expected reply, in seconds.
+    pub time_to_move: i64,
+    pondering: Option<Pondering>,
+}
+
+/// A search running on the opponent's time.
+struct Pondering {
+    reply: Plae,
+    /// The Zobrist hash of the position after the reply.
+    key: u64,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<(Box<dyn AI + Send>, Option<Plae>)>,
+}
+
+impl AiPonder {
+    #[must_use]
+    pub fn new(ai: Box<dyn AI + Send>) -> Self {
+        Self {
+            ai: Some(ai),
+            time_to_move: 15,
+            pondering: None,
+        }
+    }
+
     #[must_use]
-    pub fn hash(&self, board: &Board, side_to_play: Role) -> u64 {
-        let mut hash = 0u64;
+    pub fn is_pondering(&self) -> bool {
+        self.pondering.is_some()
+    }
+
+    /// Starts pondering the reply the AI expects to `play`.
+    fn ponder_reply(&mut self, game: &Game, play: &Plae) {
+        let Some(reply) = self.ai.as_ref().and_then(|ai| ai.expected_reply()) else {
+            return;
+        };
+
+        let mut game = game.clone();
+        if game.play(play).is_err() || game.play(&reply).is_err() || game.status != Status::Ongoing
+        {
+            return;
+        }
+        let Some(mut ai) = self.ai.take() else {
+            return;
+        };
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let key = ZOBRIST.hash(&game.board, game.turn);
+        let thread = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                let play = ai.ponder(&game, &stop);
+                (ai, play)
+            })
+        };
+
+        self.pondering = Some(Pondering {
+            reply,
+            key,
+            stop,
+            thread,
+        });
+    }
+
+    /// Stops pondering and takes the AI back, with the play the pondering search found.
+    fn stop(&mut self) -> Option<Plae> {
+        let pondering = self.pondering.take()?;
+        pondering.stop.store(true, Ordering::Relaxed);
+
+        let (ai, play) = pondering.thread.join().ok()?;
+        self.ai = Some(ai);
+        play
+    }
+}
+
+impl AI for AiPonder {
+    fn generate_move(&mut self, game: &Game) -> Option<Plae> {
+        let mut play = None;
+
+        if let Some(pondering) = &self.pondering {
+            if pondering.key == ZOBRIST.hash(&game.board, game.turn) {
+                let deadline = Instant::now() + time_budget(game, self.time_to_move);
+                while !pondering.thread.is_finished() && Instant::now() < deadline {
+                    thread::sleep(Duration::from_millis(10));
+                }
+
+                play = self.stop();
+            } else {
+                self.stop();
+            }
+        }
+
+        let play = match play {
+            Some(play) => play,
+            None => self.ai.as_mut()?.generate_move(game)?,
+        };
+        self.ponder_reply(game, &play);
+
+        Some(play)
+    }
+
+    fn expected_reply(&self) -> Option<Plae> {
+        match &self.pondering {
+            Some(pondering) => Some(pondering.reply.clone()),
+            None => self.ai.as_ref()?.expected_reply(),
+        }
+    }
+}
+
+impl Drop for AiPonder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// An engine in another process that speaks the text protocol on its standard input and output,
+/// like hnefatafl-text-protocol. It is told the plays of the game and the clock before each move.
+pub struct AiProcess {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    /// The plays the engine was told since its position was set.
+    plays: Option<Vec<Plae>>,
+}
+
+impl AiProcess {
+    /// Starts `command`, a program and its arguments split on whitespace.
+    ///
+    /// # Errors
+    ///
+    /// If the command is empty or doesn't start.
+    pub fn new(command: &str) -> anyhow::Result<Self> {
+        let mut words = command.split_ascii_whitespace();
+        let program = words.next().context("the engine command is empty")?;
+
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start {program}"))?;
+
+        let stdin = child.stdin.take().context("the engine has no stdin")?;
+        let stdout = child.stdout.take().context("the engine has no stdout")?;
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+            plays: None,
+        })
+    }
+
+    /// Sends a command and returns the response without the `= `. Lines that aren't responses,
+    /// like info lines, are skipped.
+    ///
+    /// # Errors
+    ///
+    /// If the engine can't be talked to or it answers with an error.
+    pub fn send(&mut self, command: &str) -> anyhow::Result<String> {
+        writeln!(self.stdin, "{}", command.trim())?;
+        self.stdin.flush()?;
+
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.stdout.read_line(&mut line)? == 0 {
+                return Err(anyhow::Error::msg("the engine closed its output"));
+            }
+
+            let line = line.trim();
+            if let Some(response) = line.strip_prefix('=') {
+                return Ok(response.trim().to_string());
+            }
+            if let Some(error) = line.strip_prefix('?') {
+                return Err(anyhow::Error::msg(format!("{command}: {}", error.trim())));
+            }
+        }
+    }
+
+    /// Tells the engine the plays it hasn't heard of, from the start of the game if it doesn't
+    /// know the game, and the clock of the side to play.
+    fn sync(&mut self, game: &Game) -> anyhow::Result<()> {
+        let plays: Vec<_> = game
+            .plays
+            .0
+            .iter()
+            .filter_map(|record| record.play.clone())
+            .collect();
+
+        let sent = match &self.plays {
+            Some(sent) if plays.starts_with(sent) => sent.len(),
+            _ => {
+                self.send(&format!("set_position {}", Position::start(game)))?;
+                0
+            }
+        };
+
+        for play in &plays[sent..] {
+            self.send(&play.to_string())?;
+        }
+        self.plays = Some(plays);
+
+        let time = match game.turn {
+            Role::Attacker => &game.attacker_time,
+            Role::Defender | Role::Roleless => &game.defender_time,
+        };
+        let time_settings = match time {
+            TimeSettings::Timed(time) => {
+                let mut left = time.milliseconds_left;
+                if let TimeUnix::Time(played_at) = game.time {
+                    left -= Utc::now().timestamp_millis() - played_at;
+                }
+
+                format!("time_settings fischer {} {}", left.max(0), time.add_seconds)
+            }
+            TimeSettings::UnTimed => "time_settings un-timed".to_string(),
+        };
+        self.send(&time_settings)?;
 
-        if side_to_play == Role::Defender {
-            hash ^= self.defender_to_move_bits;
+        Ok(())
+    }
+
+    fn play(&mut self, game: &Game) -> anyhow::Result<Plae> {
+        self.sync(game)?;
+
+        let response = self.send("generate_move")?;
+        let args: Vec<_> = response.split_ascii_whitespace().collect();
+        Plae::parse(&args, game.board.size)
+    }
+}
+
+impl AI for AiProcess {
+    fn generate_move(&mut self, game: &Game) -> Option<Plae> {
+        if game.status != Status::Ongoing {
+            return None;
         }
 
-        for (i, space) in board.spaces.iter().enumerate() {
-            if space != &Space::Empty {
-                let j = space.index();
-                hash ^= self.piece_bits[i][j];
+        match self.play(game) {
+            Ok(play) => Some(play),
+            Err(error) => {
+                log::error!("{error}");
+                // Start over with the next play.
+                self.plays = None;
+                None
             }
         }
+    }
+}
 
-        hash
+impl Drop for AiProcess {
+    fn drop(&mut self) {
+        // The engine may already be gone.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
     }
 }
+
+/// A position in the `AiMcts` tree.
+#[derive(Clone, Debug)]
+struct Node {
+    parent: Option<usize>,
+    /// The play that reached the position.
+    play: Option<Plae>,
+    /// The side to play in the position.
+    turn: Role,
+    children: Vec<usize>,
+    untried: Vec<Plae>,
+    visits: u32,
+    /// The sum of the playout results for the defender.
+    defender_wins: f64,
+}
+
+impl Node {
+    fn new(parent: Option<usize>, play: Option<Plae>, game: &Game) -> Self {
+        let untried = if game.status == Status::Ongoing {
+            game.all_legal_plays()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            parent,
+            play,
+            turn: game.turn,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            defender_wins: 0.0,
+        }
+    }
+}
+
+/// How long to think about the next move, see `AiBasic::time_budget`.
+fn time_budget(game: &Game, time_to_move: i64) -> Duration {
+    let most = time_to_move.saturating_mul(1_000);
+
+    let time = match game.turn {
+        Role::Attacker => &game.attacker_time,
+        Role::Defender => &game.defender_time,
+        Role::Roleless => return Duration::ZERO,
+    };
+
+    let milliseconds = match time {
+        TimeSettings::Timed(time) => {
+            let mut left = time.milliseconds_left;
+            // The clock only counts down when a play is made.
+            if let TimeUnix::Time(played_at) = game.time {
+                left -= Utc::now().timestamp_millis() - played_at;
+            }
+            let left = left.max(0);
+
+            (left / MOVES_TO_GO + time.add_seconds * 750)
+                .min(left / 2)
+                .min(most)
+        }
+        TimeSettings::UnTimed => most,
+    };
+
+    Duration::from_millis(u64::try_from(milliseconds).unwrap_or_default())
+}
@@ -1,16 +1,20 @@
 use std::{
     io::{self, BufRead, BufReader, Write},
     net::TcpStream,
+    path::PathBuf,
     str::FromStr,
+    sync::Arc,
     thread,
 };
 
 use anyhow::Error;
-use clap::{CommandFactory, Parser, command};
+use clap::{CommandFactory, Parser};
 use hnefatafl_copenhagen::{
     COPYRIGHT, LONG_VERSION, VERSION_ID,
-    ai::{AI, AiBanal, AiBasic},
+    ai::{AiKind, AiSettings, MAX_SKILL},
+    evaluation::Evaluator,
     game::Game,
+    opening_book::OpeningBook,
     play::Vertex,
     role::Role,
     status::Status,
@@ -40,9 +44,33 @@ struct Args {
     #[arg(default_value = "hnefatafl.org", long)]
     host: String,
 
-    /// Choose an AI to play as
+    /// Choose an AI to play as: banal, basic, or mcts
     #[arg(default_value = "banal", long)]
-    ai: String,
+    ai: AiKind,
+
+    /// The size of the basic AI's transposition table in megabytes
+    #[arg(long)]
+    hash: Option<usize>,
+
+    /// How many threads the basic AI searches with
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Load the evaluation weights from a RON file
+    #[arg(long)]
+    weights: Option<PathBuf>,
+
+    /// How well the basic AI plays, from 1 to 20
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=i64::from(MAX_SKILL)))]
+    skill: Option<u8>,
+
+    /// Think on the opponent's time about the reply the AI expects
+    #[arg(long)]
+    ponder: bool,
+
+    /// Play from an opening book built by hnefatafl-book before searching
+    #[arg(long)]
+    book: Option<PathBuf>,
 
     /// Challenge the AI with AI CHALLENGER
     #[arg(long)]
@@ -68,10 +96,30 @@ fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
+    let evaluator = match &args.weights {
+        Some(path) => Some(Evaluator::from_path(path)?),
+        None => None,
+    };
+    let book = match &args.book {
+        Some(path) => Some(Arc::new(OpeningBook::from_path(path)?)),
+        None => None,
+    };
+    let ai = AiSettings {
+        kind: args.ai,
+        skill: args.skill,
+        threads: args.threads,
+        hash: args.hash,
+        evaluator,
+        book,
+        ponder: args.ponder,
+        ..AiSettings::default()
+    };
+    ai.check()?;
+
     let mut username = "ai-".to_string();
     username.push_str(&args.username);
 
-    let mut address = args.host.to_string();
+    let mut address = args.host.clone();
     address.push_str(PORT);
 
     let mut buf = String::new();
@@ -91,7 +139,7 @@ fn main() -> anyhow::Result<()> {
         buf.clear();
 
         let game_id_2 = game_id.clone();
-        let ai = args.ai;
+        let ai_2 = ai.with_kind(AiKind::from_str(&ai_2)?);
         let tcp_clone = tcp.try_clone()?;
         thread::spawn(move || accept_challenger(&ai, &mut reader, &mut buf, &mut tcp, &game_id));
 
@@ -105,9 +153,7 @@ fn main() -> anyhow::Result<()> {
 
         tcp_2.write_all(format!("join_game_pending {game_id_2}\n").as_bytes())?;
         let tcp_2_clone = tcp_2.try_clone()?;
-        thread::spawn(move || {
-            handle_messages(ai_2.as_str(), &game_id_2, &mut reader_2, &mut tcp_2, true)
-        });
+        thread::spawn(move || handle_messages(&ai_2, &game_id_2, &mut reader_2, &mut tcp_2, true));
 
         let mut buffer = String::new();
         io::stdin().read_line(&mut buffer)?;
@@ -123,7 +169,7 @@ fn main() -> anyhow::Result<()> {
 
             wait_for_challenger(&mut reader, &mut buf, &mut tcp, &game_id)?;
 
-            handle_messages(args.ai.as_str(), &game_id, &mut reader, &mut tcp, true)?;
+            handle_messages(&ai, &game_id, &mut reader, &mut tcp, true)?;
         }
     }
 
@@ -131,7 +177,7 @@ fn main() -> anyhow::Result<()> {
 }
 
 fn accept_challenger(
-    ai: &str,
+    ai: &AiSettings,
     reader: &mut BufReader<TcpStream>,
     buf: &mut String,
     tcp: &mut TcpStream,
@@ -198,14 +244,14 @@ fn wait_for_challenger(
 }
 
 fn handle_messages(
-    ai: &str,
+    ai: &AiSettings,
     game_id: &str,
     reader: &mut BufReader<TcpStream>,
     tcp: &mut TcpStream,
     io_on: bool,
 ) -> anyhow::Result<()> {
     let mut game = Game::default();
-    let mut ai = choose_ai(ai)?;
+    let mut ai = ai.build(None);
 
     if io_on {
         println!("{game}\n");
@@ -222,8 +268,8 @@ fn handle_messages(
         let message: Vec<_> = buf.split_ascii_whitespace().collect();
 
         if Some("generate_move") == message.get(2).copied() {
-            let play = game
-                .generate_move(&mut ai)
+            let play = ai
+                .generate_move(&game)
                 .expect("the game must be in progress");
 
             game.play(&play)?;
@@ -252,14 +298,14 @@ fn handle_messages(
             if from == "resigns" {
                 return Ok(());
             }
-            let Ok(from) = Vertex::from_str(from) else {
+            let Ok(from) = Vertex::parse(from, game.board.size) else {
                 panic!("expected from to be a vertex");
             };
 
             let Some(to) = message.get(5).copied() else {
                 panic!("expected to");
             };
-            let Ok(to) = Vertex::from_str(to) else {
+            let Ok(to) = Vertex::parse(to, game.board.size) else {
                 panic!("expected to to be a vertex");
             };
 
@@ -281,11 +327,3 @@ fn handle_messages(
         buf.clear();
     }
 }
-
-fn choose_ai(ai: &str) -> anyhow::Result<Box<dyn AI>> {
-    match ai {
-        "banal" => Ok(Box::new(AiBanal)),
-        "basic" => Ok(Box::new(AiBasic::default())),
-        _ => Err(anyhow::Error::msg("you didn't choose a valid AI")),
-    }
-}
@@ -0,0 +1,68 @@
+use std::{fs, path::PathBuf};
+
+use clap::Parser;
+use hnefatafl_copenhagen::{
+    game_record::game_records_from_path,
+    opening_book::{DEFAULT_PLAYS, OpeningBook},
+    play::Plae,
+    server_game::ArchivedGame,
+};
+
+/// Hnefatafl Book
+///
+/// This builds an opening book for the AIs from the first plays of finished games.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Read game records from a CSV file, like tests/copenhagen.csv
+    #[arg(long)]
+    csv: Vec<PathBuf>,
+
+    /// Read archived games from a server RON file, like hnefatafl-games.ron
+    #[arg(long)]
+    archive: Vec<PathBuf>,
+
+    /// Write the book to a file, as RON if it ends in .ron and as postcard otherwise
+    #[arg(default_value = "hnefatafl-book.postcard", long)]
+    output: PathBuf,
+
+    /// How many plays into each game to go
+    #[arg(default_value_t = DEFAULT_PLAYS, long)]
+    plays: usize,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let mut book = OpeningBook::default();
+    let mut games = 0;
+
+    for path in &args.csv {
+        for record in game_records_from_path(path)? {
+            let plays = record.plays.into_iter().map(|(play, _)| Plae::Play(play));
+            book.add_game(plays, &record.status, args.plays);
+            games += 1;
+        }
+    }
+
+    for path in &args.archive {
+        for line in fs::read_to_string(path)?.lines() {
+            let game: ArchivedGame = ron::from_str(line)?;
+            let plays = game.plays.into_iter().filter_map(|record| record.play);
+            book.add_game(plays, &game.status, args.plays);
+            games += 1;
+        }
+    }
+
+    if book.positions.is_empty() {
+        return Err(anyhow::Error::msg(
+            "there are no finished games to learn from",
+        ));
+    }
+    println!("games: {games}");
+    println!("positions: {}", book.positions.len());
+
+    book.save(&args.output)?;
+    println!("wrote {}", args.output.display());
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::Write,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use clap::Parser;
+use hnefatafl_copenhagen::{
+    ai::{AI, AiProcess, AiSettings},
+    engine_match::{self, DEFAULT_MAX_PLAYS, Score},
+    game::{Game, TimeUnix},
+    position::Position,
+    role::Role,
+    time::TimeSettings,
+};
+
+/// Hnefatafl Match
+///
+/// This plays games between two AIs, each playing attacker in half of them, and reports the
+/// score. An AI is `NAME [KEY=VALUE ...]` as the ai command of the text protocol takes it, like
+/// `basic depth=4 time=1`, or `process:COMMAND ARGS` for an engine that speaks the text protocol,
+/// like `process:hnefatafl-text-protocol`.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// The first AI
+    #[arg(long)]
+    engine_1: String,
+
+    /// The second AI
+    #[arg(long)]
+    engine_2: String,
+
+    /// How many games to play
+    #[arg(default_value_t = 10, long)]
+    games: usize,
+
+    /// The clock of each side: 'fischer MILLISECONDS ADD_SECONDS' or 'un-timed'
+    #[arg(default_value = "fischer 60000 1", long)]
+    time_settings: String,
+
+    /// Start the games from the positions in a file, one per line, each played twice
+    #[arg(long)]
+    openings: Option<PathBuf>,
+
+    /// How many plays a game goes before it is scored as a draw
+    #[arg(default_value_t = DEFAULT_MAX_PLAYS, long)]
+    max_plays: usize,
+
+    /// Write a record of the games to a file
+    #[arg(default_value = "hnefatafl-match.log", long)]
+    log: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let engine_1 = Engine::from_str(&args.engine_1)?;
+    let engine_2 = Engine::from_str(&args.engine_2)?;
+
+    let mut time_settings = vec!["time_settings"];
+    time_settings.extend(args.time_settings.split_ascii_whitespace());
+    let time_settings = TimeSettings::try_from(time_settings)?;
+
+    let openings = match &args.openings {
+        Some(path) => fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Position::from_str)
+            .collect::<anyhow::Result<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+
+    let mut log = File::create(&args.log)?;
+    let mut score = Score::default();
+
+    for index in 0..args.games {
+        let start = if openings.is_empty() {
+            Position::from(&Game::default())
+        } else {
+            openings[index / 2 % openings.len()].clone()
+        };
+
+        let mut game = Game::from(start.clone());
+        game.attacker_time = time_settings.clone();
+        game.defender_time = time_settings.clone();
+
+        // Each AI plays attacker from each opening once.
+        let (attacker, defender, role_1) = if index % 2 == 0 {
+            (&engine_1, &engine_2, Role::Attacker)
+        } else {
+            (&engine_2, &engine_1, Role::Defender)
+        };
+
+        let mut attacker_ai = attacker.build()?;
+        let mut defender_ai = defender.build()?;
+
+        game.time = match time_settings {
+            TimeSettings::Timed(_) => TimeUnix::default(),
+            TimeSettings::UnTimed => TimeUnix::UnTimed,
+        };
+        engine_match::play_game(
+            &mut game,
+            attacker_ai.as_mut(),
+            defender_ai.as_mut(),
+            args.max_plays,
+        );
+
+        score.outcomes.push(engine_match::outcome(&game, role_1));
+
+        let number = index + 1;
+        let tags = [
+            ("Game", number.to_string()),
+            ("Attacker", attacker.to_string()),
+            ("Defender", defender.to_string()),
+            ("TimeSettings", args.time_settings.clone()),
+        ];
+        log.write_all(engine_match::game_log(&tags, &start, &game).as_bytes())?;
+
+        let game_end = game
+            .game_end
+            .as_ref()
+            .map_or_else(String::new, |game_end| format!(" ({game_end})"));
+        println!(
+            "game {number}: {attacker} vs {defender}: {}{game_end}, score {score}",
+            game.status,
+        );
+    }
+
+    println!("{engine_1} vs {engine_2}: {score}");
+    match score.elo_difference() {
+        Some(elo) => println!("elo difference: {elo:.0}"),
+        None => println!("elo difference: unknown"),
+    }
+    println!("rating: {}", score.rating().to_string_rounded());
+    println!("wrote {}", args.log.display());
+
+    Ok(())
+}
+
+/// An AI as given on the command line.
+#[derive(Clone, Debug)]
+enum Engine {
+    Settings(AiSettings),
+    /// A program and its arguments.
+    Process(String),
+}
+
+impl Engine {
+    /// A fresh AI for a game.
+    fn build(&self) -> anyhow::Result<Box<dyn AI>> {
+        match self {
+            Self::Settings(ai) => Ok(ai.build(None)),
+            Self::Process(command) => Ok(Box::new(AiProcess::new(command)?)),
+        }
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Settings(ai) => write!(f, "{ai}"),
+            Self::Process(command) => write!(f, "process:{command}"),
+        }
+    }
+}
+
+impl FromStr for Engine {
+    type Err = anyhow::Error;
+
+    fn from_str(engine: &str) -> anyhow::Result<Self> {
+        if let Some(command) = engine.strip_prefix("process:") {
+            Ok(Self::Process(command.trim().to_string()))
+        } else {
+            Ok(Self::Settings(AiSettings::from_str(engine)?))
+        }
+    }
+}
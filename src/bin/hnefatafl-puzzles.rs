@@ -0,0 +1,124 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::PathBuf,
+};
+
+use clap::Parser;
+use hnefatafl_copenhagen::{
+    game::{Game, TimeUnix},
+    game_record::game_records_from_path,
+    play::Plae,
+    puzzle::PuzzleFinder,
+    server_game::ArchivedGame,
+    time::TimeSettings,
+};
+
+/// Hnefatafl Puzzles
+///
+/// This replays finished games and writes the positions where the side to play had a forced
+/// king escape, king capture, or capture sequence with only one play that starts it, one puzzle
+/// per line.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Read archived games from a server RON file, like hnefatafl-games.ron
+    #[arg(long)]
+    archive: Vec<PathBuf>,
+
+    /// Read game records from a CSV file, like tests/copenhagen.csv
+    #[arg(long)]
+    csv: Vec<PathBuf>,
+
+    /// Write the puzzles to a file
+    #[arg(default_value = "hnefatafl-puzzles.txt", long)]
+    output: PathBuf,
+
+    /// How many positions the solver looks at for a forced win
+    #[arg(default_value_t = PuzzleFinder::default().solver_nodes, long)]
+    solver_nodes: u64,
+
+    /// How deep the search looks for captures
+    #[arg(default_value_t = PuzzleFinder::default().depth, long)]
+    depth: u64,
+
+    /// How many more pieces than it loses the side to play has to capture
+    #[arg(default_value_t = PuzzleFinder::default().captures, long)]
+    captures: usize,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let finder = PuzzleFinder {
+        solver_nodes: args.solver_nodes,
+        depth: args.depth,
+        captures: args.captures,
+        ..PuzzleFinder::default()
+    };
+
+    let mut games: Vec<Vec<Plae>> = Vec::new();
+    for path in &args.archive {
+        for line in fs::read_to_string(path)?.lines() {
+            let game: ArchivedGame = ron::from_str(line)?;
+            games.push(
+                game.plays
+                    .into_iter()
+                    .filter_map(|record| record.play)
+                    .collect(),
+            );
+        }
+    }
+    for path in &args.csv {
+        for record in game_records_from_path(path)? {
+            games.push(
+                record
+                    .plays
+                    .into_iter()
+                    .map(|(play, _)| Plae::Play(play))
+                    .collect(),
+            );
+        }
+    }
+
+    if games.is_empty() {
+        return Err(anyhow::Error::msg("there are no games to look through"));
+    }
+
+    let mut output = File::create(&args.output)?;
+    let mut puzzles = 0;
+
+    for (index, plays) in games.iter().enumerate() {
+        let mut game = Game {
+            attacker_time: TimeSettings::UnTimed,
+            defender_time: TimeSettings::UnTimed,
+            time: TimeUnix::UnTimed,
+            ..Game::default()
+        };
+        let mut found = 0;
+        // The plays left of the solution of the last puzzle, the positions along it are the
+        // same puzzle.
+        let mut skip = 0;
+
+        for play in plays {
+            if skip > 0 {
+                skip -= 1;
+            } else if let Some(puzzle) = finder.find(&game) {
+                writeln!(output, "{puzzle}")?;
+                skip = puzzle.solution.len();
+                found += 1;
+            }
+
+            if game.play(play).is_err() {
+                break;
+            }
+        }
+
+        puzzles += found;
+        println!("game {}: {found} puzzles", index + 1);
+    }
+
+    println!("puzzles: {puzzles}");
+    println!("wrote {}", args.output.display());
+
+    Ok(())
+}
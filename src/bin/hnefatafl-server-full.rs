@@ -17,13 +17,14 @@ use std::fmt::Write as _;
 
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use chrono::{Local, Utc};
-use clap::{CommandFactory, Parser, command};
+use clap::{CommandFactory, Parser};
 use env_logger::Builder;
 use hnefatafl_copenhagen::{
     COPYRIGHT, LONG_VERSION, VERSION_ID,
     accounts::{Account, Accounts, Email},
     draw::Draw,
     game::TimeUnix,
+    game_end::GameEnd,
     glicko::Outcome,
     handle_error,
     rating::Rated,
@@ -743,51 +744,56 @@ impl Server {
             }
 
             game.game.status = Status::Draw;
+            game.game.game_end = Some(GameEnd::Agreement);
 
-            let accounts = &mut self.accounts.0;
-            let (attacker_rating, defender_rating) = if let (Some(attacker), Some(defender)) =
-                (accounts.get(&game.attacker), accounts.get(&game.defender))
-            {
-                (attacker.rating.rating, defender.rating.rating)
-            } else {
-                panic!("the attacker and defender accounts should exist");
-            };
+            self.draw_game(id, game);
+        }
 
-            if let Some(attacker) = accounts.get_mut(&game.attacker) {
-                attacker.draws += 1;
+        None
+    }
 
-                if game.rated.into() {
-                    attacker
-                        .rating
-                        .update_rating(defender_rating, &Outcome::Draw);
-                }
-            }
-            if let Some(defender) = accounts.get_mut(&game.defender) {
-                defender.draws += 1;
+    /// Counts a draw for both players, rates it if the game is rated, and archives the game.
+    fn draw_game(&mut self, id: usize, game: ServerGame) {
+        let accounts = &mut self.accounts.0;
+        let (attacker_rating, defender_rating) = if let (Some(attacker), Some(defender)) =
+            (accounts.get(&game.attacker), accounts.get(&game.defender))
+        {
+            (attacker.rating.rating, defender.rating.rating)
+        } else {
+            panic!("the attacker and defender accounts should exist");
+        };
 
-                if game.rated.into() {
-                    defender
-                        .rating
-                        .update_rating(attacker_rating, &Outcome::Draw);
-                }
-            }
+        if let Some(attacker) = accounts.get_mut(&game.attacker) {
+            attacker.draws += 1;
 
-            if let Some(game) = self.games_light.0.get_mut(&id) {
-                game.game_over = true;
+            if game.rated.into() {
+                attacker
+                    .rating
+                    .update_rating(defender_rating, &Outcome::Draw);
             }
+        }
+        if let Some(defender) = accounts.get_mut(&game.defender) {
+            defender.draws += 1;
 
-            if !self.skip_the_data_file {
-                self.append_archived_game(game)
-                    .map_err(|err| {
-                        error!("{err}");
-                    })
-                    .ok()?;
+            if game.rated.into() {
+                defender
+                    .rating
+                    .update_rating(attacker_rating, &Outcome::Draw);
             }
+        }
 
-            self.save_server();
+        if let Some(game) = self.games_light.0.get_mut(&id) {
+            game.game_over = true;
         }
 
-        None
+        if !self.skip_the_data_file {
+            if let Err(err) = self.append_archived_game(game) {
+                error!("{err}");
+                return;
+            }
+        }
+
+        self.save_server();
     }
 
     #[allow(clippy::too_many_lines)]
@@ -925,7 +931,13 @@ impl Server {
                     }
                 }
 
-                let message = format!("= game_over {index} attacker_wins");
+                let message = format!(
+                    "= game_over {index} attacker_wins {}",
+                    game.game
+                        .game_end
+                        .map(|game_end| game_end.to_string())
+                        .unwrap_or_default()
+                );
                 let _ok = game.attacker_tx.send(message.clone());
                 let _ok = game.defender_tx.send(message.clone());
 
@@ -956,7 +968,29 @@ impl Server {
                 return None;
             }
             Status::Draw => {
-                // Handled in the draw fn.
+                let message = format!(
+                    "= game_over {index} draw {}",
+                    game.game
+                        .game_end
+                        .map(|game_end| game_end.to_string())
+                        .unwrap_or_default()
+                );
+                let _ok = game.attacker_tx.send(message.clone());
+                let _ok = game.defender_tx.send(message.clone());
+
+                for spectator in game_light.spectators.values() {
+                    if let Some(sender) = self.clients.get(spectator) {
+                        let _ok = sender.send(message.clone());
+                    }
+                }
+
+                let Some(game) = self.games.0.remove(&index) else {
+                    panic!("the game should exist")
+                };
+
+                self.draw_game(index, game);
+
+                return None;
             }
             Status::Ongoing => {
                 if attackers_turn_next {
@@ -998,7 +1032,13 @@ impl Server {
                     }
                 }
 
-                let message = format!("= game_over {index} defender_wins");
+                let message = format!(
+                    "= game_over {index} defender_wins {}",
+                    game.game
+                        .game_end
+                        .map(|game_end| game_end.to_string())
+                        .unwrap_or_default()
+                );
                 let _ok = game.attacker_tx.send(message.clone());
                 let _ok = game.defender_tx.send(message.clone());
 
@@ -2138,7 +2178,7 @@ mod tests {
 
         tcp_1.write_all(b"game 0 play attacker resigns _\n")?;
         reader_1.read_line(&mut buf)?;
-        assert_eq!(buf, "= game_over 0 defender_wins\n");
+        assert_eq!(buf, "= game_over 0 defender_wins resignation\n");
         buf.clear();
 
         reader_2.read_line(&mut buf)?;
@@ -2146,7 +2186,7 @@ mod tests {
         buf.clear();
 
         reader_2.read_line(&mut buf)?;
-        assert_eq!(buf, "= game_over 0 defender_wins\n");
+        assert_eq!(buf, "= game_over 0 defender_wins resignation\n");
         buf.clear();
 
         Ok(())
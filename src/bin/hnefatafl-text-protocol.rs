@@ -2,14 +2,17 @@ use std::{
     io::{self, BufReader},
     net::TcpStream,
     process::{Command, ExitStatus},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
 };
 
-use clap::command;
 use clap::{self, Parser};
 
 use hnefatafl_copenhagen::{
-    ai::{AI, AiBanal},
+    ai::{AI, AiSettings, SearchInfo},
     game::Game,
+    play_error::error_message,
     read_response,
     status::Status,
     write_command,
@@ -25,6 +28,14 @@ struct Args {
     #[arg(default_value_t = false, long)]
     display_game: bool,
 
+    /// The AI generate_move uses, like 'basic depth=4 seed=1', see the ai command
+    #[arg(default_value = "banal", long)]
+    ai: AiSettings,
+
+    /// Print an info line each time the search of generate_move or analyze finishes a depth
+    #[arg(default_value_t = false, long)]
+    info: bool,
+
     /// Listen for HTP drivers on host and port
     #[arg(long, value_name = "host:port")]
     tcp: Option<String>,
@@ -32,7 +43,6 @@ struct Args {
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
-    let mut ai: Box<dyn AI + 'static> = Box::new(AiBanal);
 
     if let Some(tcp) = args.tcp {
         let address = tcp.as_str();
@@ -40,7 +50,11 @@ fn main() -> anyhow::Result<()> {
         println!("connected to {address} ...");
 
         let mut reader = BufReader::new(stream.try_clone()?);
-        let mut game = Game::default();
+        let mut game = Game {
+            ai: args.ai.clone(),
+            ..Game::default()
+        };
+        let mut ai: Box<dyn AI> = game.ai.build(None);
 
         for i in 1..10_000 {
             println!("\n*** turn {i} ***");
@@ -80,7 +94,17 @@ fn main() -> anyhow::Result<()> {
     let mut buffer = String::new();
     let stdin = io::stdin();
 
-    let mut game = Game::default();
+    let mut game = Game {
+        ai: args.ai,
+        ..Game::default()
+    };
+
+    let mut info = None;
+    if args.info {
+        let (sender, receiver) = mpsc::channel();
+        game.info = Some(sender);
+        info = Some(receiver);
+    }
 
     if args.display_game {
         #[cfg(any(target_family = "unix", target_family = "windows"))]
@@ -95,7 +119,10 @@ fn main() -> anyhow::Result<()> {
             buffer.clear();
             continue;
         }
-        let result = game.read_line(&buffer);
+        let result = match &info {
+            Some(info) => read_line_with_info(&mut game, &buffer, info),
+            None => game.read_line(&buffer),
+        };
 
         if args.display_game {
             #[cfg(any(target_family = "unix", target_family = "windows"))]
@@ -104,7 +131,7 @@ fn main() -> anyhow::Result<()> {
         }
 
         match result {
-            Err(error) => println!("? {error}\n"),
+            Err(error) => println!("? {}\n", error_message(&error)),
             Ok(message) => {
                 if let Some(message) = message {
                     println!("= {message}\n");
@@ -116,6 +143,30 @@ fn main() -> anyhow::Result<()> {
     }
 }
 
+/// Reads the line on another thread and prints the info lines the search sends while it runs.
+fn read_line_with_info(
+    game: &mut Game,
+    buffer: &str,
+    info: &Receiver<SearchInfo>,
+) -> anyhow::Result<Option<String>> {
+    thread::scope(|scope| {
+        let read_line = scope.spawn(|| game.read_line(buffer));
+
+        while !read_line.is_finished() {
+            if let Ok(info) = info.recv_timeout(Duration::from_millis(10)) {
+                println!("{info}");
+            }
+        }
+        for info in info.try_iter() {
+            println!("{info}");
+        }
+
+        read_line
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::Error::msg("the engine panicked")))
+    })
+}
+
 fn clear_screen() -> anyhow::Result<ExitStatus> {
     #[cfg(target_family = "unix")]
     let exit_status = Command::new("clear").status()?;
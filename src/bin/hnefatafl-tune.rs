@@ -0,0 +1,85 @@
+use std::{fs, path::PathBuf};
+
+use clap::Parser;
+use hnefatafl_copenhagen::{
+    evaluation::Evaluator,
+    game_record::game_records_from_path,
+    play::Plae,
+    server_game::ArchivedGame,
+    tuning::{self, Sample},
+};
+
+/// Hnefatafl Tune
+///
+/// This fits the evaluation weights of the AIs to the results of finished games.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    /// Read game records from a CSV file, like tests/copenhagen.csv
+    #[arg(long)]
+    csv: Vec<PathBuf>,
+
+    /// Read archived games from a server RON file, like hnefatafl-games.ron
+    #[arg(long)]
+    archive: Vec<PathBuf>,
+
+    /// Start from the weights in a RON file instead of the default weights
+    #[arg(long)]
+    weights: Option<PathBuf>,
+
+    /// Write the tuned weights to a RON file
+    #[arg(default_value = "hnefatafl-weights.ron", long)]
+    output: PathBuf,
+
+    /// The most passes over the weights
+    #[arg(default_value_t = 1_000, long)]
+    passes: usize,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let mut samples: Vec<Sample> = Vec::new();
+
+    for path in &args.csv {
+        for record in game_records_from_path(path)? {
+            let plays = record.plays.into_iter().map(|(play, _)| Plae::Play(play));
+            samples.extend(tuning::samples(plays, &record.status));
+        }
+    }
+
+    for path in &args.archive {
+        for line in fs::read_to_string(path)?.lines() {
+            let game: ArchivedGame = ron::from_str(line)?;
+            let plays = game.plays.into_iter().filter_map(|record| record.play);
+            samples.extend(tuning::samples(plays, &game.status));
+        }
+    }
+
+    if samples.is_empty() {
+        return Err(anyhow::Error::msg(
+            "there are no finished games to learn from",
+        ));
+    }
+    println!("samples: {}", samples.len());
+
+    let evaluator = match &args.weights {
+        Some(path) => Evaluator::from_path(path)?,
+        None => Evaluator::default(),
+    };
+
+    let k = tuning::fit_k(&evaluator, &samples);
+    println!("k: {k}");
+    println!("error: {}", tuning::error(&evaluator, &samples, k));
+
+    let (evaluator, error) = tuning::tune(&evaluator, &samples, k, args.passes);
+    println!("error: {error}");
+
+    let pretty = ron::ser::PrettyConfig::default();
+    fs::write(
+        &args.output,
+        ron::ser::to_string_pretty(&evaluator, pretty)?,
+    )?;
+    println!("wrote {}", args.output.display());
+
+    Ok(())
+}
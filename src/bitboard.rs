@@ -0,0 +1,458 @@
+use std::{
+    hash::{Hash, Hasher},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not},
+};
+
+use crate::{board_size::BoardSize, play::Vertex};
+
+/// Enough words for the 361 squares of a 19×19 board.
+const WORDS: usize = 6;
+
+/// How many squares fit in a `BitBoard::Small`, enough for an 11×11 board.
+const SMALL: usize = 128;
+
+/// The masks for each `BoardSize`, in the order of its variants.
+pub(crate) static MASKS: [Masks; 7] = [
+    Masks::new(7),
+    Masks::new(9),
+    Masks::new(11),
+    Masks::new(13),
+    Masks::new(15),
+    Masks::new(17),
+    Masks::new(19),
+];
+
+/// The squares that matter to the rules on one size of board.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Masks {
+    /// Every square on the board.
+    pub board: BitBoard,
+    /// The middle square, only the king may stand on it.
+    pub throne: BitBoard,
+    /// The four corners, the king escapes by reaching one.
+    pub corners: BitBoard,
+    /// The throne and the corners.
+    pub restricted: BitBoard,
+    /// The squares touching a wall.
+    pub edges: BitBoard,
+    first_file: BitBoard,
+    last_file: BitBoard,
+}
+
+impl Masks {
+    const fn new(size: usize) -> Self {
+        let last = size - 1;
+        let mut board = BitBoard::EMPTY;
+        let mut first_file = BitBoard::EMPTY;
+        let mut last_file = BitBoard::EMPTY;
+        let mut edges = BitBoard::EMPTY;
+
+        let mut i = 0;
+        while i < size {
+            let mut j = 0;
+            while j < size {
+                board = board.or(BitBoard::bit(i * size + j, size));
+                j += 1;
+            }
+
+            first_file = first_file.or(BitBoard::bit(i * size, size));
+            last_file = last_file.or(BitBoard::bit(i * size + last, size));
+            edges = edges
+                .or(BitBoard::bit(i * size, size))
+                .or(BitBoard::bit(i * size + last, size))
+                .or(BitBoard::bit(i, size))
+                .or(BitBoard::bit(last * size + i, size));
+            i += 1;
+        }
+
+        let throne = BitBoard::bit((size / 2) * size + size / 2, size);
+        let corners = BitBoard::bit(0, size)
+            .or(BitBoard::bit(last, size))
+            .or(BitBoard::bit(last * size, size))
+            .or(BitBoard::bit(last * size + last, size));
+
+        Self {
+            board,
+            throne,
+            corners,
+            restricted: corners.or(throne),
+            edges,
+            first_file,
+            last_file,
+        }
+    }
+}
+
+/// A set of squares, one bit per square, indexed by `y * size + x`.
+///
+/// The squares of a board up to 11×11 fit in one `u128`, the usual boards don't pay for the
+/// words a 19×19 board needs.
+#[derive(Clone, Copy, Debug)]
+pub enum BitBoard {
+    /// A board of up to `SMALL` squares.
+    Small(u128),
+    /// A board of up to 19×19 squares.
+    Large([u64; WORDS]),
+}
+
+impl Default for BitBoard {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl PartialEq for BitBoard {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Small(bits_1), Self::Small(bits_2)) => bits_1 == bits_2,
+            _ => self.words() == other.words(),
+        }
+    }
+}
+
+impl Eq for BitBoard {}
+
+impl Hash for BitBoard {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.words().hash(state);
+    }
+}
+
+impl BitBoard {
+    const EMPTY: Self = Self::Small(0);
+
+    /// The set with no squares on a board of `size`.
+    #[must_use]
+    pub const fn empty(size: BoardSize) -> Self {
+        if size.get() * size.get() <= SMALL {
+            Self::EMPTY
+        } else {
+            Self::Large([0; WORDS])
+        }
+    }
+
+    /// The set with only the square at `index` on a board of `size` squares a side.
+    const fn bit(index: usize, size: usize) -> Self {
+        if size * size <= SMALL {
+            Self::Small(1 << index)
+        } else {
+            let mut words = [0; WORDS];
+            words[index / 64] = 1 << (index % 64);
+            Self::Large(words)
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    const fn words(self) -> [u64; WORDS] {
+        match self {
+            Self::Small(bits) => [bits as u64, (bits >> 64) as u64, 0, 0, 0, 0],
+            Self::Large(words) => words,
+        }
+    }
+
+    const fn or(self, rhs: Self) -> Self {
+        if let (Self::Small(bits_1), Self::Small(bits_2)) = (self, rhs) {
+            return Self::Small(bits_1 | bits_2);
+        }
+
+        let mut words = self.words();
+        let rhs = rhs.words();
+        let mut i = 0;
+        while i < WORDS {
+            words[i] |= rhs[i];
+            i += 1;
+        }
+        Self::Large(words)
+    }
+
+    const fn and(self, rhs: Self) -> Self {
+        if let (Self::Small(bits_1), Self::Small(bits_2)) = (self, rhs) {
+            return Self::Small(bits_1 & bits_2);
+        }
+
+        let mut words = self.words();
+        let rhs = rhs.words();
+        let mut i = 0;
+        while i < WORDS {
+            words[i] &= rhs[i];
+            i += 1;
+        }
+        Self::Large(words)
+    }
+
+    const fn and_not(self, rhs: Self) -> Self {
+        if let (Self::Small(bits_1), Self::Small(bits_2)) = (self, rhs) {
+            return Self::Small(bits_1 & !bits_2);
+        }
+
+        let mut words = self.words();
+        let rhs = rhs.words();
+        let mut i = 0;
+        while i < WORDS {
+            words[i] &= !rhs[i];
+            i += 1;
+        }
+        Self::Large(words)
+    }
+
+    /// Moves every square `n` indexes up, `n` is less than 64.
+    const fn shift_left(self, n: usize) -> Self {
+        let words = match self {
+            Self::Small(bits) => return Self::Small(bits << n),
+            Self::Large(words) => words,
+        };
+
+        let mut shifted = [0; WORDS];
+        let mut i = 0;
+        while i < WORDS {
+            shifted[i] = words[i] << n;
+            if i > 0 {
+                shifted[i] |= words[i - 1] >> (64 - n);
+            }
+            i += 1;
+        }
+        Self::Large(shifted)
+    }
+
+    /// Moves every square `n` indexes down, `n` is less than 64.
+    const fn shift_right(self, n: usize) -> Self {
+        let words = match self {
+            Self::Small(bits) => return Self::Small(bits >> n),
+            Self::Large(words) => words,
+        };
+
+        let mut shifted = [0; WORDS];
+        let mut i = 0;
+        while i < WORDS {
+            shifted[i] = words[i] >> n;
+            if i + 1 < WORDS {
+                shifted[i] |= words[i + 1] << (64 - n);
+            }
+            i += 1;
+        }
+        Self::Large(shifted)
+    }
+
+    #[must_use]
+    pub const fn square(vertex: &Vertex) -> Self {
+        Self::bit(vertex.y * vertex.size.get() + vertex.x, vertex.size.get())
+    }
+
+    #[must_use]
+    pub const fn contains(self, vertex: &Vertex) -> bool {
+        let index = vertex.y * vertex.size.get() + vertex.x;
+        match self {
+            Self::Small(bits) => bits & (1 << index) != 0,
+            Self::Large(words) => words[index / 64] & (1 << (index % 64)) != 0,
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub const fn count(self) -> u16 {
+        let words = match self {
+            // There are at most 128 squares in a word.
+            Self::Small(bits) => return bits.count_ones() as u16,
+            Self::Large(words) => words,
+        };
+
+        let mut count = 0;
+        let mut i = 0;
+        while i < WORDS {
+            count += words[i].count_ones() as u16;
+            i += 1;
+        }
+        count
+    }
+
+    #[must_use]
+    pub fn first(self, size: BoardSize) -> Option<Vertex> {
+        self.indexes().next().map(|index| vertex(index, size))
+    }
+
+    pub fn insert(&mut self, vertex: &Vertex) {
+        *self = self.or(Self::square(vertex));
+    }
+
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        let words = match self {
+            Self::Small(bits) => return bits == 0,
+            Self::Large(words) => words,
+        };
+
+        let mut i = 0;
+        while i < WORDS {
+            if words[i] != 0 {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    /// The indexes of the squares in the set, from the top left to the bottom right.
+    pub fn indexes(self) -> impl Iterator<Item = usize> {
+        let (mut small, mut words) = match self {
+            Self::Small(bits) => (bits, [0; WORDS]),
+            Self::Large(words) => (0, words),
+        };
+        let mut i = 0;
+
+        std::iter::from_fn(move || {
+            if small != 0 {
+                let index = small.trailing_zeros() as usize;
+                small &= small - 1;
+                return Some(index);
+            }
+
+            while i < WORDS {
+                let bits = &mut words[i];
+                if *bits != 0 {
+                    let index = i * 64 + bits.trailing_zeros() as usize;
+                    *bits &= *bits - 1;
+                    return Some(index);
+                }
+                i += 1;
+            }
+            None
+        })
+    }
+
+    pub fn remove(&mut self, vertex: &Vertex) {
+        *self = self.and_not(Self::square(vertex));
+    }
+
+    pub fn vertexes(self, size: BoardSize) -> impl Iterator<Item = Vertex> {
+        self.indexes().map(move |index| vertex(index, size))
+    }
+
+    #[must_use]
+    pub fn up(self, size: BoardSize) -> Self {
+        self.shift_right(size.get())
+    }
+
+    #[must_use]
+    pub fn left(self, size: BoardSize) -> Self {
+        self.shift_right(1).and_not(size.masks().last_file)
+    }
+
+    #[must_use]
+    pub fn down(self, size: BoardSize) -> Self {
+        self.shift_left(size.get()).and(size.masks().board)
+    }
+
+    #[must_use]
+    pub fn right(self, size: BoardSize) -> Self {
+        let masks = size.masks();
+        self.shift_left(1)
+            .and_not(masks.first_file)
+            .and(masks.board)
+    }
+
+    /// The squares orthogonally next to a square in the set.
+    #[must_use]
+    pub fn neighbors(self, size: BoardSize) -> Self {
+        self.up(size) | self.left(size) | self.down(size) | self.right(size)
+    }
+
+    /// Grows the set through the `passable` squares till it stops changing.
+    #[must_use]
+    pub fn flood_fill(self, passable: Self, size: BoardSize) -> Self {
+        let masks = size.masks();
+        if let (
+            Self::Small(mut filled),
+            Self::Small(passable),
+            Self::Small(board),
+            Self::Small(first_file),
+            Self::Small(last_file),
+        ) = (
+            self,
+            passable,
+            masks.board,
+            masks.first_file,
+            masks.last_file,
+        ) {
+            // The same as below in one word, the fills run after every play.
+            let size = size.get();
+            let passable = passable & board;
+
+            loop {
+                let neighbors = (filled >> size)
+                    | ((filled >> 1) & !last_file)
+                    | (filled << size)
+                    | ((filled << 1) & !first_file);
+                let next = filled | (neighbors & passable);
+                if next == filled {
+                    return Self::Small(filled);
+                }
+                filled = next;
+            }
+        }
+
+        let mut filled = self;
+
+        loop {
+            let next = filled | (filled.neighbors(size) & passable);
+            if next == filled {
+                return filled;
+            }
+            filled = next;
+        }
+    }
+}
+
+impl BitAnd for BitBoard {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        self.and(rhs)
+    }
+}
+
+impl BitAndAssign for BitBoard {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = self.and(rhs);
+    }
+}
+
+impl BitOr for BitBoard {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.or(rhs)
+    }
+}
+
+impl BitOrAssign for BitBoard {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.or(rhs);
+    }
+}
+
+/// Every square not in the set, including the bits past the edge of the board. Mask the result
+/// with `Masks::board` before counting it.
+impl Not for BitBoard {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Self::Small(bits) => Self::Small(!bits),
+            Self::Large(mut words) => {
+                for word in &mut words {
+                    *word = !*word;
+                }
+                Self::Large(words)
+            }
+        }
+    }
+}
+
+#[must_use]
+fn vertex(index: usize, size: BoardSize) -> Vertex {
+    Vertex {
+        size,
+        x: index % size.get(),
+        y: index / size.get(),
+    }
+}
@@ -1,17 +1,37 @@
-use std::{collections::HashMap, fmt};
+use std::{collections::HashMap, fmt, str::FromStr};
 
-use rustc_hash::{FxBuildHasher, FxHashSet};
 use serde::{Deserialize, Serialize};
-use serde_with::serde_as;
 
 use crate::{
+    bitboard::BitBoard,
+    board_size::BoardSize,
     game::PreviousBoards,
+    game_end::GameEnd,
     play::{BOARD_LETTERS, Plae, Play, Vertex},
+    play_error::PlayError,
     role::Role,
+    rules::{KingCapture, RuleSet},
     space::Space,
     status::Status,
+    zobrist::ZOBRIST,
 };
 
+pub const BRANDUBH: [&str; 7] = [
+    "...X...", "...X...", "...O...", "XXOKOXX", "...O...", "...X...", "...X...",
+];
+
+pub const TABLUT: [&str; 9] = [
+    "...XXX...",
+    "....X....",
+    "....O....",
+    "X...O...X",
+    "XXOOKOOXX",
+    "X...O...X",
+    "....O....",
+    "....X....",
+    "...XXX...",
+];
+
 pub const STARTING_POSITION: [&str; 11] = [
     "...XXXXX...",
     ".....X.....",
@@ -26,28 +46,289 @@ pub const STARTING_POSITION: [&str; 11] = [
     "...XXXXX...",
 ];
 
-const EXIT_SQUARES: [Vertex; 4] = [
-    Vertex { x: 0, y: 0 },
-    Vertex { x: 10, y: 0 },
-    Vertex { x: 0, y: 10 },
-    Vertex { x: 10, y: 10 },
+pub const TAWLBWRDD: [&str; 11] = [
+    "....XXX....",
+    "....X.X....",
+    ".....X.....",
+    ".....O.....",
+    "XX..OOO..XX",
+    "X.XOOKOOX.X",
+    "XX..OOO..XX",
+    ".....O.....",
+    ".....X.....",
+    "....X.X....",
+    "....XXX....",
 ];
 
-const THRONE: Vertex = Vertex { x: 5, y: 5 };
+pub const HNEFATAFL_13: [&str; 13] = [
+    "....XXXXX....",
+    "......X......",
+    ".............",
+    "......O......",
+    "X.....O.....X",
+    "X.....O.....X",
+    "XX.OOOKOOO.XX",
+    "X.....O.....X",
+    "X.....O.....X",
+    "......O......",
+    ".............",
+    "......X......",
+    "....XXXXX....",
+];
 
-const RESTRICTED_SQUARES: [Vertex; 5] = [
-    Vertex { x: 0, y: 0 },
-    Vertex { x: 10, y: 0 },
-    Vertex { x: 0, y: 10 },
-    Vertex { x: 10, y: 10 },
-    THRONE,
+pub const ALEA_EVANGELII: [&str; 19] = [
+    ".......XXXXX.......",
+    ".........X.........",
+    "....X.........X....",
+    "...X...........X...",
+    "..X.X...X.X...X.X..",
+    "...................",
+    ".........O.........",
+    "X.......OOO.......X",
+    "X...X..OOOOO..X...X",
+    "XX....OOOKOOO....XX",
+    "X...X..OOOOO..X...X",
+    "X.......OOO.......X",
+    ".........O.........",
+    "...................",
+    "..X.X...X.X...X.X..",
+    "...X...........X...",
+    "....X.........X....",
+    ".........X.........",
+    ".......XXXXX.......",
 ];
 
-#[serde_as]
+/// The starting positions the engine knows, by the game they come from.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum StartingPosition {
+    Brandubh,
+    Tablut,
+    #[default]
+    Copenhagen,
+    Tawlbwrdd,
+    Hnefatafl13,
+    AleaEvangelii,
+}
+
+impl StartingPosition {
+    #[must_use]
+    pub fn rows(self) -> &'static [&'static str] {
+        match self {
+            Self::Brandubh => &BRANDUBH,
+            Self::Tablut => &TABLUT,
+            Self::Copenhagen => &STARTING_POSITION,
+            Self::Tawlbwrdd => &TAWLBWRDD,
+            Self::Hnefatafl13 => &HNEFATAFL_13,
+            Self::AleaEvangelii => &ALEA_EVANGELII,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If the starting position is invalid.
+    #[must_use]
+    pub fn board(self) -> Board {
+        self.rows()
+            .try_into()
+            .expect("the starting positions are valid")
+    }
+}
+
+/// The usual starting position for a board of `size`, 15×15 and 17×17 boards have none.
+impl TryFrom<BoardSize> for StartingPosition {
+    type Error = anyhow::Error;
+
+    fn try_from(size: BoardSize) -> anyhow::Result<Self> {
+        match size {
+            BoardSize::_7 => Ok(Self::Brandubh),
+            BoardSize::_9 => Ok(Self::Tablut),
+            BoardSize::_11 => Ok(Self::Copenhagen),
+            BoardSize::_13 => Ok(Self::Hnefatafl13),
+            BoardSize::_19 => Ok(Self::AleaEvangelii),
+            BoardSize::_15 | BoardSize::_17 => Err(anyhow::Error::msg(format!(
+                "there is no starting position for a {size}×{size} board"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for StartingPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Brandubh => write!(f, "brandubh"),
+            Self::Tablut => write!(f, "tablut"),
+            Self::Copenhagen => write!(f, "copenhagen"),
+            Self::Tawlbwrdd => write!(f, "tawlbwrdd"),
+            Self::Hnefatafl13 => write!(f, "hnefatafl_13"),
+            Self::AleaEvangelii => write!(f, "alea_evangelii"),
+        }
+    }
+}
+
+impl FromStr for StartingPosition {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "brandubh" => Ok(Self::Brandubh),
+            "tablut" => Ok(Self::Tablut),
+            "copenhagen" => Ok(Self::Copenhagen),
+            "tawlbwrdd" => Ok(Self::Tawlbwrdd),
+            "hnefatafl_13" => Ok(Self::Hnefatafl13),
+            "alea_evangelii" => Ok(Self::AleaEvangelii),
+            _ => Err(anyhow::Error::msg(format!(
+                "invalid starting position: {value}"
+            ))),
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(try_from = "Spaces", into = "Spaces")]
 pub struct Board {
-    #[serde_as(as = "[_; 121]")]
-    pub spaces: [Space; 11 * 11],
+    pub size: BoardSize,
+    pub attackers: BitBoard,
+    pub defenders: BitBoard,
+    pub king: BitBoard,
+    /// The Zobrist key of the pieces, kept up to date as they move.
+    pub key: u64,
+}
+
+/// How a `Board` is serialized, one `Space` per square.
+#[derive(Deserialize, Serialize)]
+#[serde(rename = "Board")]
+struct Spaces {
+    #[serde(with = "spaces_tuple")]
+    spaces: Vec<Space>,
+}
+
+/// Serializes the spaces as a tuple, the way 11×11 boards always were.
+///
+/// Binary formats don't write the length of a tuple, so there a board of another size starts with
+/// its size. Sizes are bigger than the index of any `Space`, which tells the two apart.
+mod spaces_tuple {
+    use std::fmt;
+
+    use serde::{
+        Deserializer, Serializer,
+        de::{self, SeqAccess, Visitor},
+        ser::{self, SerializeTuple},
+    };
+
+    use crate::{board_size::BoardSize, space::Space};
+
+    const MAX_SQUARES: usize = 19 * 19;
+
+    pub fn serialize<S: Serializer>(spaces: &[Space], serializer: S) -> Result<S::Ok, S::Error> {
+        let size = spaces.len().isqrt();
+        let sized = !serializer.is_human_readable() && size != BoardSize::_11.get();
+        let mut tuple = serializer.serialize_tuple(spaces.len() + usize::from(sized))?;
+
+        if sized {
+            tuple.serialize_element(
+                &u32::try_from(size).map_err(<S::Error as ser::Error>::custom)?,
+            )?;
+        }
+        for space in spaces {
+            tuple.serialize_element(space)?;
+        }
+
+        tuple.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Space>, D::Error> {
+        let human_readable = deserializer.is_human_readable();
+        deserializer.deserialize_tuple(MAX_SQUARES + 1, SpacesVisitor { human_readable })
+    }
+
+    struct SpacesVisitor {
+        human_readable: bool,
+    }
+
+    impl<'de> Visitor<'de> for SpacesVisitor {
+        type Value = Vec<Space>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a tuple of the spaces on a board")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<Space>, A::Error> {
+            if self.human_readable {
+                let mut spaces = Vec::new();
+                while let Some(space) = seq.next_element()? {
+                    spaces.push(space);
+                }
+                return Ok(spaces);
+            }
+
+            let first: u32 = seq
+                .next_element()?
+                .ok_or_else(|| <A::Error as de::Error>::invalid_length(0, &self))?;
+
+            let (size, mut spaces) = match first {
+                0 => (BoardSize::_11, vec![Space::Empty]),
+                1 => (BoardSize::_11, vec![Space::Attacker]),
+                2 => (BoardSize::_11, vec![Space::King]),
+                3 => (BoardSize::_11, vec![Space::Defender]),
+                size => (
+                    BoardSize::try_from(size as usize).map_err(<A::Error as de::Error>::custom)?,
+                    Vec::new(),
+                ),
+            };
+
+            while spaces.len() < size.get() * size.get() {
+                let space = seq
+                    .next_element()?
+                    .ok_or_else(|| <A::Error as de::Error>::invalid_length(spaces.len(), &self))?;
+                spaces.push(space);
+            }
+
+            Ok(spaces)
+        }
+    }
+}
+
+impl TryFrom<Spaces> for Board {
+    type Error = anyhow::Error;
+
+    fn try_from(spaces: Spaces) -> anyhow::Result<Self> {
+        let size = BoardSize::try_from(spaces.spaces.len().isqrt())?;
+        if size.get() * size.get() != spaces.spaces.len() {
+            return Err(anyhow::Error::msg("the board has to be square"));
+        }
+
+        let mut board = Self::empty_board(size);
+
+        for (i, space) in spaces.spaces.into_iter().enumerate() {
+            board.set(
+                &Vertex {
+                    size,
+                    x: i % size.get(),
+                    y: i / size.get(),
+                },
+                space,
+            );
+        }
+
+        Ok(board)
+    }
+}
+
+impl From<Board> for Spaces {
+    fn from(board: Board) -> Self {
+        let size = board.size.get();
+        let spaces = (0..size * size)
+            .map(|i| {
+                board.get(&Vertex {
+                    size: board.size,
+                    x: i % size,
+                    y: i / size,
+                })
+            })
+            .collect();
+
+        Self { spaces }
+    }
 }
 
 impl Default for Board {
@@ -59,11 +340,15 @@ impl Default for Board {
 impl fmt::Debug for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f)?;
-        for y in 0..11 {
+        for y in 0..self.size.get() {
             write!(f, r#"""#)?;
 
-            for x in 0..11 {
-                match self.spaces[(y * 10) + x] {
+            for x in 0..self.size.get() {
+                match self.get(&Vertex {
+                    size: self.size,
+                    x,
+                    y,
+                }) {
                     Space::Attacker => write!(f, "X")?,
                     Space::Empty => write!(f, ".")?,
                     Space::King => write!(f, "K")?,
@@ -79,26 +364,29 @@ impl fmt::Debug for Board {
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut letters = " ".repeat(3).to_string();
-        letters.push_str(BOARD_LETTERS);
-        let bar = "─".repeat(11);
+        let size = self.size.get();
+        let mut letters = " ".repeat(3);
+        letters.push_str(&BOARD_LETTERS[..size]);
+        let bar = "─".repeat(size);
+        let restricted = self.size.masks().restricted;
 
         writeln!(f, "\n{letters}\n  ┌{bar}┐")?;
-        for y in 0..11 {
-            let y_label = 11 - y;
-            write!(f, "{y_label:2}│",)?;
-
-            for x in 0..11 {
-                if ((y, x) == (0, 0)
-                    || (y, x) == (10, 0)
-                    || (y, x) == (0, 10)
-                    || (y, x) == (10, 10)
-                    || (y, x) == (5, 5))
-                    && self.spaces[y * 11 + x] == Space::Empty
-                {
+        for y in 0..size {
+            let y_label = size - y;
+            write!(f, "{y_label:2}│")?;
+
+            for x in 0..size {
+                let vertex = Vertex {
+                    size: self.size,
+                    x,
+                    y,
+                };
+                let space = self.get(&vertex);
+
+                if restricted.contains(&vertex) && space == Space::Empty {
                     write!(f, "⌘")?;
                 } else {
-                    write!(f, "{}", self.spaces[y * 11 + x])?;
+                    write!(f, "{space}")?;
                 }
             }
             writeln!(f, "│{y_label:2}")?;
@@ -107,20 +395,36 @@ impl fmt::Display for Board {
     }
 }
 
-impl TryFrom<[&str; 11]> for Board {
+impl<const N: usize> TryFrom<[&str; N]> for Board {
+    type Error = anyhow::Error;
+
+    fn try_from(value: [&str; N]) -> anyhow::Result<Self> {
+        value.as_slice().try_into()
+    }
+}
+
+impl TryFrom<&[&str]> for Board {
     type Error = anyhow::Error;
 
-    fn try_from(value: [&str; 11]) -> anyhow::Result<Self> {
-        let mut spaces = [Space::Empty; 11 * 11];
-        let mut kings = 0;
+    fn try_from(value: &[&str]) -> anyhow::Result<Self> {
+        let size = BoardSize::try_from(value.len())?;
+        let restricted = size.masks().restricted;
+        let mut board = Self::empty_board(size);
 
         for (y, row) in value.iter().enumerate() {
+            if row.chars().count() != size.get() {
+                return Err(anyhow::Error::msg(format!(
+                    "Every row has to have {size} squares!"
+                )));
+            }
+
             for (x, ch) in row.chars().enumerate() {
                 let space = ch.try_into()?;
+                let vertex = Vertex { size, x, y };
+
                 match space {
                     Space::Attacker | Space::Defender => {
-                        let vertex = Vertex { x, y };
-                        if RESTRICTED_SQUARES.contains(&vertex) {
+                        if restricted.contains(&vertex) {
                             return Err(anyhow::Error::msg(
                                 "Only the king is allowed on restricted squares!",
                             ));
@@ -128,48 +432,23 @@ impl TryFrom<[&str; 11]> for Board {
                     }
                     Space::Empty => {}
                     Space::King => {
-                        kings += 1;
-                        if kings > 1 {
+                        if !board.king.is_empty() {
                             return Err(anyhow::Error::msg("You can only have one king!"));
                         }
                     }
                 }
 
-                spaces[y * 11 + x] = space;
+                board.set(&vertex, space);
             }
         }
 
-        Ok(Self { spaces })
+        Ok(board)
     }
 }
 
 impl Board {
     fn able_to_move(&self, play_from: &Vertex) -> bool {
-        if let Some(vertex) = play_from.up() {
-            if self.get(&vertex) == Space::Empty {
-                return true;
-            }
-        }
-
-        if let Some(vertex) = play_from.left() {
-            if self.get(&vertex) == Space::Empty {
-                return true;
-            }
-        }
-
-        if let Some(vertex) = play_from.down() {
-            if self.get(&vertex) == Space::Empty {
-                return true;
-            }
-        }
-
-        if let Some(vertex) = play_from.right() {
-            if self.get(&vertex) == Space::Empty {
-                return true;
-            }
-        }
-
-        false
+        !(BitBoard::square(play_from).neighbors(self.size) & self.empty()).is_empty()
     }
 
     #[must_use]
@@ -178,124 +457,70 @@ impl Board {
         status: &Status,
         turn: &Role,
         previous_boards: &PreviousBoards,
+        rules: &RuleSet,
     ) -> bool {
-        let mut possible_vertexes = Vec::new();
-
-        for y in 0..11 {
-            for x in 0..11 {
-                let vertex = Vertex { x, y };
-                if self.get(&vertex).role() == *turn {
-                    possible_vertexes.push(vertex);
-                }
-            }
-        }
-
-        if possible_vertexes.is_empty() {
-            return false;
-        }
-
-        for vertex_from in possible_vertexes {
-            for y in 0..11 {
-                for x in 0..11 {
-                    let vertex_to = Vertex { x, y };
-                    let play = Play {
-                        role: *turn,
-                        from: vertex_from.clone(),
-                        to: vertex_to,
-                    };
-
-                    if let Ok(_board_captures_status) =
-                        self.play_internal(&Plae::Play(play), status, turn, previous_boards)
-                    {
-                        return true;
-                    }
-                }
-            }
-        }
-
-        false
+        *status == Status::Ongoing
+            && self
+                .legal_moves(*turn, previous_boards, rules)
+                .next()
+                .is_some()
     }
 
     #[must_use]
     pub fn captured(&self) -> Captured {
-        let mut attacker = 0;
-        let mut defender = 0;
-        let mut king = true;
-
-        for space in self.spaces {
-            match space {
-                Space::Attacker => attacker += 1,
-                Space::Empty => {}
-                Space::King => king = false,
-                Space::Defender => defender += 1,
-            }
-        }
+        // Without a starting position to compare with nothing counts as captured. A position
+        // set up by hand can have more pieces than the start.
+        let start = StartingPosition::try_from(self.size)
+            .map_or_else(|_| self.clone(), StartingPosition::board);
 
         Captured {
-            attacker: 24 - attacker,
-            defender: 12 - defender,
-            king,
+            attacker: start
+                .attackers
+                .count()
+                .saturating_sub(self.attackers.count()),
+            defender: start
+                .defenders
+                .count()
+                .saturating_sub(self.defenders.count()),
+            king: self.king.is_empty(),
         }
     }
 
-    #[allow(clippy::collapsible_if)]
-    fn captures(&mut self, play_to: &Vertex, role_from: Role, captures: &mut Vec<Vertex>) {
-        if let Some(up_1) = play_to.up() {
-            let space = self.get(&up_1);
-            if space != Space::King && space.role() == role_from.opposite() {
-                if let Some(up_2) = up_1.up() {
-                    if (RESTRICTED_SQUARES.contains(&up_2) && self.get(&up_2) != Space::King)
-                        || self.get(&up_2).role() == role_from
-                    {
-                        if self.set_if_not_king(&up_1, Space::Empty) {
-                            captures.push(up_1);
-                        }
-                    }
-                }
+    fn captures(
+        &mut self,
+        play_to: &Vertex,
+        role_from: Role,
+        rules: &RuleSet,
+        captures: &mut Vec<Vertex>,
+    ) {
+        let played = BitBoard::square(play_to);
+        let mut pieces = self.pieces(role_from);
+        if !rules.armed_king {
+            if self.king.contains(play_to) {
+                return;
             }
+            pieces &= !self.king;
         }
 
-        if let Some(left_1) = play_to.left() {
-            let space = self.get(&left_1);
-            if space != Space::King && space.role() == role_from.opposite() {
-                if let Some(left_2) = left_1.left() {
-                    if (RESTRICTED_SQUARES.contains(&left_2) && self.get(&left_2) != Space::King)
-                        || self.get(&left_2).role() == role_from
-                    {
-                        if self.set_if_not_king(&left_1, Space::Empty) {
-                            captures.push(left_1);
-                        }
-                    }
-                }
-            }
-        }
+        // A restricted square is hostile to everyone unless the king is on it.
+        let hostile = pieces | (rules.restricted(self.size) & !self.king);
+        let victims = match role_from {
+            Role::Attacker => self.defenders,
+            Role::Defender => self.attackers,
+            Role::Roleless => return,
+        };
 
-        if let Some(down_1) = play_to.down() {
-            let space = self.get(&down_1);
-            if space != Space::King && space.role() == role_from.opposite() {
-                if let Some(down_2) = down_1.down() {
-                    if (RESTRICTED_SQUARES.contains(&down_2) && self.get(&down_2) != Space::King)
-                        || self.get(&down_2).role() == role_from
-                    {
-                        if self.set_if_not_king(&down_1, Space::Empty) {
-                            captures.push(down_1);
-                        }
-                    }
-                }
-            }
-        }
-
-        if let Some(right_1) = play_to.right() {
-            let space = self.get(&right_1);
-            if space != Space::King && space.role() == role_from.opposite() {
-                if let Some(right_2) = right_1.right() {
-                    if (RESTRICTED_SQUARES.contains(&right_2) && self.get(&right_2) != Space::King)
-                        || self.get(&right_2).role() == role_from
-                    {
-                        if self.set_if_not_king(&right_1, Space::Empty) {
-                            captures.push(right_1);
-                        }
-                    }
+        for step in [
+            BitBoard::up,
+            BitBoard::left,
+            BitBoard::down,
+            BitBoard::right,
+        ] {
+            let victim = step(played, self.size) & victims;
+            if !victim.is_empty() && !(step(victim, self.size) & hostile).is_empty() {
+                if let Some(vertex) = victim.first(self.size) {
+                    self.set(&vertex, Space::Empty);
+                    captures.push(vertex);
                 }
             }
         }
@@ -308,22 +533,39 @@ impl Board {
         &mut self,
         role_from: Role,
         vertex_to: &Vertex,
+        rules: &RuleSet,
         captures: &mut Vec<Vertex>,
     ) {
+        let size = self.size;
+        let last = size.get() - 1;
+        let restricted = rules.restricted(size);
+
         // bottom row
-        for x_1 in 0..11 {
-            let vertex_1 = Vertex { x: x_1, y: 10 };
-            if self.get(&vertex_1).role() == role_from || RESTRICTED_SQUARES.contains(&vertex_1) {
+        for x_1 in 0..=last {
+            let vertex_1 = Vertex {
+                size,
+                x: x_1,
+                y: last,
+            };
+            if self.get(&vertex_1).role() == role_from || restricted.contains(&vertex_1) {
                 let mut count = 0;
 
-                if x_1 == 10 {
+                if x_1 == last {
                     break;
                 }
                 let start = x_1 + 1;
 
-                for x_2 in start..11 {
-                    let vertex_2 = Vertex { x: x_2, y: 10 };
-                    let vertex_3 = Vertex { x: x_2, y: 9 };
+                for x_2 in start..=last {
+                    let vertex_2 = Vertex {
+                        size,
+                        x: x_2,
+                        y: last,
+                    };
+                    let vertex_3 = Vertex {
+                        size,
+                        x: x_2,
+                        y: last - 1,
+                    };
                     let role_2 = self.get(&vertex_2).role();
                     let role_3 = self.get(&vertex_3).role();
                     if role_2 == role_from.opposite() && role_3 == role_from {
@@ -334,18 +576,32 @@ impl Board {
                 }
 
                 let finish = start + count;
-                let vertex = Vertex { x: finish, y: 10 };
+                let vertex = Vertex {
+                    size,
+                    x: finish,
+                    y: last,
+                };
                 let role = self.get(&vertex).role();
-                if count > 1 && (role == role_from || RESTRICTED_SQUARES.contains(&vertex)) {
+                if count > 1 && (role == role_from || restricted.contains(&vertex)) {
                     if vertex_to
                         == &(Vertex {
+                            size,
                             x: start - 1,
-                            y: 10,
+                            y: last,
                         })
-                        || vertex_to == &(Vertex { x: finish, y: 10 })
+                        || vertex_to
+                            == &(Vertex {
+                                size,
+                                x: finish,
+                                y: last,
+                            })
                     {
                         for x_2 in start..finish {
-                            let vertex = Vertex { x: x_2, y: 10 };
+                            let vertex = Vertex {
+                                size,
+                                x: x_2,
+                                y: last,
+                            };
                             if self.set_if_not_king(&vertex, Space::Empty) {
                                 captures.push(vertex);
                             }
@@ -356,19 +612,19 @@ impl Board {
         }
 
         // top row
-        for x_1 in 0..11 {
-            let vertex_1 = Vertex { x: x_1, y: 0 };
-            if self.get(&vertex_1).role() == role_from || RESTRICTED_SQUARES.contains(&vertex_1) {
+        for x_1 in 0..=last {
+            let vertex_1 = Vertex { size, x: x_1, y: 0 };
+            if self.get(&vertex_1).role() == role_from || restricted.contains(&vertex_1) {
                 let mut count = 0;
 
-                if x_1 == 10 {
+                if x_1 == last {
                     break;
                 }
                 let start = x_1 + 1;
 
-                for x_2 in start..11 {
-                    let vertex_2 = Vertex { x: x_2, y: 0 };
-                    let vertex_3 = Vertex { x: x_2, y: 1 };
+                for x_2 in start..=last {
+                    let vertex_2 = Vertex { size, x: x_2, y: 0 };
+                    let vertex_3 = Vertex { size, x: x_2, y: 1 };
                     let role_2 = self.get(&vertex_2).role();
                     let role_3 = self.get(&vertex_3).role();
                     if role_2 == role_from.opposite() && role_3 == role_from {
@@ -379,14 +635,28 @@ impl Board {
                 }
 
                 let finish = start + count;
-                let vertex = Vertex { x: finish, y: 0 };
+                let vertex = Vertex {
+                    size,
+                    x: finish,
+                    y: 0,
+                };
                 let role = self.get(&vertex).role();
-                if count > 1 && (role == role_from || RESTRICTED_SQUARES.contains(&vertex)) {
-                    if vertex_to == &(Vertex { x: start - 1, y: 0 })
-                        || vertex_to == &(Vertex { x: finish, y: 0 })
+                if count > 1 && (role == role_from || restricted.contains(&vertex)) {
+                    if vertex_to
+                        == &(Vertex {
+                            size,
+                            x: start - 1,
+                            y: 0,
+                        })
+                        || vertex_to
+                            == &(Vertex {
+                                size,
+                                x: finish,
+                                y: 0,
+                            })
                     {
                         for x_2 in start..finish {
-                            let vertex = Vertex { x: x_2, y: 0 };
+                            let vertex = Vertex { size, x: x_2, y: 0 };
                             if self.set_if_not_king(&vertex, Space::Empty) {
                                 captures.push(vertex);
                             }
@@ -397,19 +667,19 @@ impl Board {
         }
 
         // left row
-        for y_1 in 0..11 {
-            let vertex_1 = Vertex { x: 0, y: y_1 };
-            if self.get(&vertex_1).role() == role_from || RESTRICTED_SQUARES.contains(&vertex_1) {
+        for y_1 in 0..=last {
+            let vertex_1 = Vertex { size, x: 0, y: y_1 };
+            if self.get(&vertex_1).role() == role_from || restricted.contains(&vertex_1) {
                 let mut count = 0;
 
-                if y_1 == 10 {
+                if y_1 == last {
                     break;
                 }
                 let start = y_1 + 1;
 
-                for y_2 in start..11 {
-                    let vertex_2 = Vertex { x: 0, y: y_2 };
-                    let vertex_3 = Vertex { x: 1, y: y_2 };
+                for y_2 in start..=last {
+                    let vertex_2 = Vertex { size, x: 0, y: y_2 };
+                    let vertex_3 = Vertex { size, x: 1, y: y_2 };
                     let role_2 = self.get(&vertex_2).role();
                     let role_3 = self.get(&vertex_3).role();
                     if role_2 == role_from.opposite() && role_3 == role_from {
@@ -420,14 +690,28 @@ impl Board {
                 }
 
                 let finish = start + count;
-                let vertex = Vertex { x: 0, y: finish };
+                let vertex = Vertex {
+                    size,
+                    x: 0,
+                    y: finish,
+                };
                 let role = self.get(&vertex).role();
-                if count > 1 && (role == role_from || RESTRICTED_SQUARES.contains(&vertex)) {
-                    if vertex_to == &(Vertex { x: 0, y: start - 1 })
-                        || vertex_to == &(Vertex { x: 0, y: finish })
+                if count > 1 && (role == role_from || restricted.contains(&vertex)) {
+                    if vertex_to
+                        == &(Vertex {
+                            size,
+                            x: 0,
+                            y: start - 1,
+                        })
+                        || vertex_to
+                            == &(Vertex {
+                                size,
+                                x: 0,
+                                y: finish,
+                            })
                     {
                         for y_2 in start..finish {
-                            let vertex = Vertex { x: 0, y: y_2 };
+                            let vertex = Vertex { size, x: 0, y: y_2 };
                             if self.set_if_not_king(&vertex, Space::Empty) {
                                 captures.push(vertex);
                             }
@@ -438,19 +722,31 @@ impl Board {
         }
 
         // right row
-        for y_1 in 0..11 {
-            let vertex_1 = Vertex { x: 10, y: y_1 };
-            if self.get(&vertex_1).role() == role_from || RESTRICTED_SQUARES.contains(&vertex_1) {
+        for y_1 in 0..=last {
+            let vertex_1 = Vertex {
+                size,
+                x: last,
+                y: y_1,
+            };
+            if self.get(&vertex_1).role() == role_from || restricted.contains(&vertex_1) {
                 let mut count = 0;
 
-                if y_1 == 10 {
+                if y_1 == last {
                     break;
                 }
                 let start = y_1 + 1;
 
-                for y_2 in start..11 {
-                    let vertex_2 = Vertex { x: 10, y: y_2 };
-                    let vertex_3 = Vertex { x: 9, y: y_2 };
+                for y_2 in start..=last {
+                    let vertex_2 = Vertex {
+                        size,
+                        x: last,
+                        y: y_2,
+                    };
+                    let vertex_3 = Vertex {
+                        size,
+                        x: last - 1,
+                        y: y_2,
+                    };
                     let role_2 = self.get(&vertex_2).role();
                     let role_3 = self.get(&vertex_3).role();
                     if role_2 == role_from.opposite() && role_3 == role_from {
@@ -461,18 +757,32 @@ impl Board {
                 }
 
                 let finish = start + count;
-                let vertex = Vertex { x: 10, y: finish };
+                let vertex = Vertex {
+                    size,
+                    x: last,
+                    y: finish,
+                };
                 let role = self.get(&vertex).role();
-                if count > 1 && (role == role_from || RESTRICTED_SQUARES.contains(&vertex)) {
+                if count > 1 && (role == role_from || restricted.contains(&vertex)) {
                     if vertex_to
                         == &(Vertex {
-                            x: 10,
+                            size,
+                            x: last,
                             y: start - 1,
                         })
-                        || vertex_to == &(Vertex { x: 10, y: finish })
+                        || vertex_to
+                            == &(Vertex {
+                                size,
+                                x: last,
+                                y: finish,
+                            })
                     {
                         for y_2 in start..finish {
-                            let vertex = Vertex { x: 10, y: y_2 };
+                            let vertex = Vertex {
+                                size,
+                                x: last,
+                                y: y_2,
+                            };
                             if self.set_if_not_king(&vertex, Space::Empty) {
                                 captures.push(vertex);
                             }
@@ -487,37 +797,34 @@ impl Board {
     ///
     /// If the vertex is out of bounds.
     pub fn find_the_king(&self) -> anyhow::Result<Option<Vertex>> {
-        for y in 0..11 {
-            for x in 0..11 {
-                let v = Vertex { x, y };
-                if self.get(&v) == Space::King {
-                    return Ok(Some(v));
-                }
-            }
-        }
-
-        Ok(None)
+        Ok(self.king.first(self.size))
     }
 
     fn capture_the_king(
         &self,
         play_to: &Vertex,
+        rules: &RuleSet,
         captures: &mut Vec<Vertex>,
-    ) -> anyhow::Result<bool> {
+    ) -> bool {
+        if rules.king_capture == KingCapture::TwoSides {
+            return self.capture_the_king_by_two(play_to, rules, captures);
+        }
+
+        let throne = self.size.masks().throne;
         let mut played_to_capture = false;
 
-        match self.find_the_king()? {
+        match self.king.first(self.size) {
             Some(kings_vertex) => {
                 if let Some(vertex) = kings_vertex.up() {
                     if play_to == &vertex {
                         played_to_capture = true;
                     }
 
-                    if vertex != THRONE && self.get(&vertex) != Space::Attacker {
-                        return Ok(false);
+                    if !throne.contains(&vertex) && !self.attackers.contains(&vertex) {
+                        return false;
                     }
                 } else {
-                    return Ok(false);
+                    return false;
                 }
 
                 if let Some(vertex) = kings_vertex.left() {
@@ -525,11 +832,11 @@ impl Board {
                         played_to_capture = true;
                     }
 
-                    if vertex != THRONE && self.get(&vertex) != Space::Attacker {
-                        return Ok(false);
+                    if !throne.contains(&vertex) && !self.attackers.contains(&vertex) {
+                        return false;
                     }
                 } else {
-                    return Ok(false);
+                    return false;
                 }
 
                 if let Some(vertex) = kings_vertex.down() {
@@ -537,11 +844,11 @@ impl Board {
                         played_to_capture = true;
                     }
 
-                    if vertex != THRONE && self.get(&vertex) != Space::Attacker {
-                        return Ok(false);
+                    if !throne.contains(&vertex) && !self.attackers.contains(&vertex) {
+                        return false;
                     }
                 } else {
-                    return Ok(false);
+                    return false;
                 }
 
                 if let Some(vertex) = kings_vertex.right() {
@@ -549,116 +856,80 @@ impl Board {
                         played_to_capture = true;
                     }
 
-                    if vertex != THRONE && self.get(&vertex) != Space::Attacker {
-                        return Ok(false);
+                    if !throne.contains(&vertex) && !self.attackers.contains(&vertex) {
+                        return false;
                     }
                 } else {
-                    return Ok(false);
+                    return false;
                 }
 
                 if played_to_capture {
                     captures.push(kings_vertex);
-                    return Ok(true);
+                    return true;
                 }
 
-                Ok(false)
+                false
             }
-            _ => Ok(false),
+            _ => false,
         }
     }
 
-    /// # Errors
-    ///
-    /// If the vertex is out of bounds.
-    fn exit_forts(&self) -> anyhow::Result<bool> {
-        match self.find_the_king()? {
-            Some(kings_vertex) => {
-                if !kings_vertex.touches_wall()
-                    || !self.able_to_move(&kings_vertex)
-                    || !self.flood_fill_defender_wins(&kings_vertex)?
-                {
-                    return Ok(false);
+    /// The king is taken like any other piece, by the attacker that moved next to it and a hostile
+    /// square on the far side.
+    fn capture_the_king_by_two(
+        &self,
+        play_to: &Vertex,
+        rules: &RuleSet,
+        captures: &mut Vec<Vertex>,
+    ) -> bool {
+        let played = BitBoard::square(play_to);
+        let hostile = self.attackers | (rules.restricted(self.size) & !self.king);
+
+        for step in [
+            BitBoard::up,
+            BitBoard::left,
+            BitBoard::down,
+            BitBoard::right,
+        ] {
+            let king = step(played, self.size) & self.king;
+            if !king.is_empty() && !(step(king, self.size) & hostile).is_empty() {
+                if let Some(vertex) = king.first(self.size) {
+                    captures.push(vertex);
+                    return true;
                 }
-
-                Ok(true)
             }
-            _ => Ok(false),
         }
+
+        false
     }
 
-    /// # Errors
-    ///
-    /// If the vertex is out of bounds.
-    fn flood_fill_attacker_wins(&self) -> anyhow::Result<bool> {
-        match self.find_the_king()? {
+    fn exit_forts(&self) -> bool {
+        match self.king.first(self.size) {
             Some(kings_vertex) => {
-                let hasher = FxBuildHasher;
-                let mut already_checked = FxHashSet::with_capacity_and_hasher(11 * 11, hasher);
-
-                already_checked.insert(kings_vertex.clone());
-                let mut stack = Vec::new();
-                stack.push(kings_vertex);
-
-                while !stack.is_empty() {
-                    if let Some(vertex) = stack.pop() {
-                        let space = self.get(&vertex);
-                        if space == Space::Empty || space.role() == Role::Defender {
-                            if !expand_flood_fill(vertex.up(), &mut already_checked, &mut stack) {
-                                return Ok(false);
-                            }
-                            if !expand_flood_fill(vertex.left(), &mut already_checked, &mut stack) {
-                                return Ok(false);
-                            }
-                            if !expand_flood_fill(vertex.down(), &mut already_checked, &mut stack) {
-                                return Ok(false);
-                            }
-                            if !expand_flood_fill(vertex.right(), &mut already_checked, &mut stack)
-                            {
-                                return Ok(false);
-                            }
-                        }
-                    }
-                }
-
-                for y in 0..11 {
-                    for x in 0..11 {
-                        let vertex = Vertex { x, y };
-                        if self.get(&vertex).role() == Role::Defender
-                            && !already_checked.contains(&vertex)
-                        {
-                            return Ok(false);
-                        }
-                    }
-                }
-
-                Ok(true)
+                self.size.masks().edges.contains(&kings_vertex)
+                    && self.able_to_move(&kings_vertex)
+                    && self.flood_fill_defender_wins(&kings_vertex)
             }
-            _ => Ok(false),
+            _ => false,
         }
     }
 
-    /// # Errors
-    ///
-    /// If the vertex is out of bounds.
-    #[allow(clippy::too_many_lines)]
-    pub fn flood_fill_defender_wins(&self, vertex: &Vertex) -> anyhow::Result<bool> {
-        let mut attacker_has_enough_pieces = false;
-        let mut count = 0;
-        'outer: for y in 0..11 {
-            for x in 0..11 {
-                let vertex = Vertex { x, y };
-                if self.get(&vertex).role() == Role::Attacker {
-                    count += 1;
-                }
-
-                if count > 1 {
-                    attacker_has_enough_pieces = true;
-                    break 'outer;
-                }
-            }
+    fn flood_fill_attacker_wins(&self) -> bool {
+        if self.king.is_empty() {
+            return false;
         }
 
-        let mut already_checked = FxHashSet::default();
+        // Everything the king's side can reach without passing an attacker.
+        let reachable = self.king.flood_fill(!self.attackers, self.size);
+
+        (reachable & self.size.masks().edges).is_empty() && (self.defenders & !reachable).is_empty()
+    }
+
+    #[allow(clippy::too_many_lines)]
+    #[must_use]
+    pub fn flood_fill_defender_wins(&self, vertex: &Vertex) -> bool {
+        let attacker_has_enough_pieces = self.attackers.count() > 1;
+        let mut already_checked = BitBoard::empty(self.size);
         let mut stack = vec![];
 
         if let Some(vertex) = vertex.up() {
@@ -680,43 +951,43 @@ impl Board {
                 if space == Space::Empty {
                     if let Some(vertex) = vertex.up() {
                         if !already_checked.contains(&vertex) {
-                            stack.push((vertex.clone(), Direction::LeftRight));
-                            already_checked.insert(vertex);
+                            already_checked.insert(&vertex);
+                            stack.push((vertex, Direction::LeftRight));
                         }
                     }
                     if let Some(vertex) = vertex.left() {
                         if !already_checked.contains(&vertex) {
-                            stack.push((vertex.clone(), Direction::UpDown));
-                            already_checked.insert(vertex);
+                            already_checked.insert(&vertex);
+                            stack.push((vertex, Direction::UpDown));
                         }
                     }
                     if let Some(vertex) = vertex.down() {
                         if !already_checked.contains(&vertex) {
-                            stack.push((vertex.clone(), Direction::LeftRight));
-                            already_checked.insert(vertex);
+                            already_checked.insert(&vertex);
+                            stack.push((vertex, Direction::LeftRight));
                         }
                     }
                     if let Some(vertex) = vertex.right() {
                         if !already_checked.contains(&vertex) {
-                            stack.push((vertex.clone(), Direction::UpDown));
-                            already_checked.insert(vertex);
+                            already_checked.insert(&vertex);
+                            stack.push((vertex, Direction::UpDown));
                         }
                     }
                 } else if space.role() == Role::Attacker {
-                    return Ok(false);
+                    return false;
                 } else if direction == Direction::UpDown {
                     let mut vertex_1 = false;
                     let mut vertex_2 = false;
 
                     if let Some(vertex) = vertex.up() {
-                        if self.get(&vertex).role() == Role::Defender {
+                        if self.pieces(Role::Defender).contains(&vertex) {
                             vertex_1 = true;
                         }
                     } else {
                         vertex_1 = true;
                     }
                     if let Some(vertex) = vertex.down() {
-                        if self.get(&vertex).role() == Role::Defender {
+                        if self.pieces(Role::Defender).contains(&vertex) {
                             vertex_2 = true;
                         }
                     } else {
@@ -724,21 +995,21 @@ impl Board {
                     }
 
                     if !vertex_1 && !vertex_2 && attacker_has_enough_pieces {
-                        return Ok(false);
+                        return false;
                     }
                 } else {
                     let mut vertex_1 = false;
                     let mut vertex_2 = false;
 
                     if let Some(vertex) = vertex.right() {
-                        if self.get(&vertex).role() == Role::Defender {
+                        if self.pieces(Role::Defender).contains(&vertex) {
                             vertex_1 = true;
                         }
                     } else {
                         vertex_1 = true;
                     }
                     if let Some(vertex) = vertex.left() {
-                        if self.get(&vertex).role() == Role::Defender {
+                        if self.pieces(Role::Defender).contains(&vertex) {
                             vertex_2 = true;
                         }
                     } else {
@@ -746,46 +1017,119 @@ impl Board {
                     }
 
                     if !vertex_1 && !vertex_2 && attacker_has_enough_pieces {
-                        return Ok(false);
+                        return false;
                     }
                 }
             }
         }
 
-        Ok(true)
+        true
     }
 
+    /// The squares the piece on `from` can slide to. A piece moves through empty squares in a
+    /// straight line, and only the king may stop on a restricted square.
     #[must_use]
-    pub fn get(&self, vertex: &Vertex) -> Space {
-        self.spaces[vertex.y * 11 + vertex.x]
+    pub fn destinations(&self, from: &Vertex, rules: &RuleSet) -> BitBoard {
+        let empty = self.empty();
+        let mut destinations = BitBoard::empty(self.size);
+
+        for step in [
+            BitBoard::up,
+            BitBoard::left,
+            BitBoard::down,
+            BitBoard::right,
+        ] {
+            let mut square = step(BitBoard::square(from), self.size) & empty;
+            while !square.is_empty() {
+                destinations |= square;
+                square = step(square, self.size) & empty;
+            }
+        }
+
+        if !self.king.contains(from) {
+            destinations &= !rules.restricted(self.size);
+        }
+
+        destinations
     }
 
+    /// Every legal move for `turn`, found by walking the rays out from each of its pieces.
+    pub fn legal_moves<'a>(
+        &'a self,
+        turn: Role,
+        previous_boards: &'a PreviousBoards,
+        rules: &'a RuleSet,
+    ) -> impl Iterator<Item = Play> + 'a {
+        let forbids_repetition = rules.forbids_repetition(turn);
+
+        self.pieces(turn)
+            .vertexes(self.size)
+            .flat_map(move |from| {
+                self.destinations(&from, rules)
+                    .vertexes(self.size)
+                    .map(move |to| Play {
+                        role: turn,
+                        from: from.clone(),
+                        to,
+                    })
+            })
+            .filter(move |play| !forbids_repetition || !self.repeats(play, previous_boards))
+    }
+
+    /// The squares without a piece on them.
     #[must_use]
-    fn no_attacker_pieces_left(&self) -> bool {
-        for y in 0..11 {
-            for x in 0..11 {
-                let v = Vertex { x, y };
-                if self.get(&v).role() == Role::Attacker {
-                    return false;
-                }
-            }
+    pub fn empty(&self) -> BitBoard {
+        self.size.masks().board & !(self.attackers | self.defenders | self.king)
+    }
+
+    #[must_use]
+    fn empty_board(size: BoardSize) -> Self {
+        Self {
+            size,
+            attackers: BitBoard::empty(size),
+            defenders: BitBoard::empty(size),
+            king: BitBoard::empty(size),
+            key: 0,
         }
+    }
 
-        true
+    #[must_use]
+    pub fn get(&self, vertex: &Vertex) -> Space {
+        if self.attackers.contains(vertex) {
+            Space::Attacker
+        } else if self.defenders.contains(vertex) {
+            Space::Defender
+        } else if self.king.contains(vertex) {
+            Space::King
+        } else {
+            Space::Empty
+        }
+    }
+
+    /// The squares with a piece belonging to `role` on them, the king is a defender.
+    #[must_use]
+    pub fn pieces(&self, role: Role) -> BitBoard {
+        match role {
+            Role::Attacker => self.attackers,
+            Role::Defender => self.defenders | self.king,
+            Role::Roleless => BitBoard::empty(self.size),
+        }
     }
 
     /// # Errors
     ///
-    /// If the vertex is out of bounds.
+    /// If the play is illegal.
     pub fn play(
         &mut self,
         play: &Plae,
         status: &Status,
         turn: &Role,
         previous_boards: &mut PreviousBoards,
-    ) -> anyhow::Result<(Vec<Vertex>, Status)> {
-        let (board, captures, status) = self.play_internal(play, status, turn, previous_boards)?;
-        previous_boards.0.insert(board.clone());
+        rules: &RuleSet,
+    ) -> Result<(Vec<Vertex>, Status), PlayError> {
+        let (board, captures, status, _game_end) =
+            self.play_internal(play, status, turn, previous_boards, rules)?;
+        previous_boards.insert(board.key);
         *self = board;
 
         Ok((captures, status))
@@ -795,7 +1139,8 @@ impl Board {
         clippy::cast_possible_truncation,
         clippy::cast_possible_wrap,
         clippy::cast_sign_loss,
-        clippy::missing_errors_doc
+        clippy::missing_errors_doc,
+        clippy::too_many_lines
     )]
     pub fn play_internal(
         &self,
@@ -803,123 +1148,198 @@ impl Board {
         status: &Status,
         turn: &Role,
         previous_boards: &PreviousBoards,
-    ) -> anyhow::Result<(Board, Vec<Vertex>, Status)> {
+        rules: &RuleSet,
+    ) -> Result<(Board, Vec<Vertex>, Status, Option<GameEnd>), PlayError> {
         if *status != Status::Ongoing {
-            return Err(anyhow::Error::msg(
-                "play: the game has to be ongoing to play",
-            ));
+            return Err(PlayError::GameOver);
         }
 
         let play = match play {
-            Plae::AttackerResigns => return Ok((self.clone(), Vec::new(), Status::DefenderWins)),
-            Plae::DefenderResigns => return Ok((self.clone(), Vec::new(), Status::AttackerWins)),
+            Plae::AttackerResigns => {
+                return Ok((
+                    self.clone(),
+                    Vec::new(),
+                    Status::DefenderWins,
+                    Some(GameEnd::Resignation),
+                ));
+            }
+            Plae::DefenderResigns => {
+                return Ok((
+                    self.clone(),
+                    Vec::new(),
+                    Status::AttackerWins,
+                    Some(GameEnd::Resignation),
+                ));
+            }
             Plae::Play(play) => play,
         };
 
+        if play.from.size != self.size || play.to.size != self.size {
+            return Err(PlayError::WrongBoardSize(self.size));
+        }
+
         let space_from = self.get(&play.from);
         let role_from = space_from.role();
 
         if role_from == Role::Roleless {
-            return Err(anyhow::Error::msg("play: you didn't select a role"));
+            return Err(PlayError::NoPiece);
         } else if *turn != role_from {
-            return Err(anyhow::Error::msg("play: it isn't your turn"));
+            return Err(PlayError::NotYourTurn);
         }
 
         let x_diff = play.from.x as i32 - play.to.x as i32;
         let y_diff = play.from.y as i32 - play.to.y as i32;
 
         if x_diff != 0 && y_diff != 0 {
-            return Err(anyhow::Error::msg(
-                "play: you can only play in a straight line",
-            ));
+            return Err(PlayError::NotStraightLine);
         }
 
         if x_diff == 0 && y_diff == 0 {
-            return Err(anyhow::Error::msg("play: you have to change location"));
+            return Err(PlayError::NoMove);
         }
 
         if x_diff != 0 {
             let x_diff_sign = x_diff.signum();
             for x_diff in 1..=x_diff.abs() {
                 let vertex = Vertex {
+                    size: self.size,
                     x: (play.from.x as i32 - (x_diff * x_diff_sign)) as usize,
                     y: play.from.y,
                 };
 
                 let space = self.get(&vertex);
                 if space != Space::Empty {
-                    return Err(anyhow::Error::msg(
-                        "play: you have to play through empty locations",
-                    ));
+                    return Err(PlayError::BlockedPath);
                 }
             }
         } else {
             let y_diff_sign = y_diff.signum();
             for y_diff in 1..=y_diff.abs() {
                 let vertex = Vertex {
+                    size: self.size,
                     x: play.from.x,
                     y: (play.from.y as i32 - (y_diff * y_diff_sign)) as usize,
                 };
                 let space = self.get(&vertex);
                 if space != Space::Empty {
-                    return Err(anyhow::Error::msg(
-                        "play: you have to play through empty locations",
-                    ));
+                    return Err(PlayError::BlockedPath);
                 }
             }
         }
 
-        if space_from != Space::King && RESTRICTED_SQUARES.contains(&play.to) {
-            return Err(anyhow::Error::msg(
-                "play: only the king may move to a restricted square",
-            ));
+        if space_from != Space::King && rules.restricted(self.size).contains(&play.to) {
+            return Err(PlayError::RestrictedSquare);
         }
 
-        let mut board = self.clone();
-        board.set(&play.from, Space::Empty);
-        board.set(&play.to, space_from);
+        let mut board = self.moved(play);
 
-        if previous_boards.0.contains(&board) && turn != &Role::Attacker {
-            return Err(anyhow::Error::msg(
-                "play: you already reached that position",
-            ));
+        if rules.forbids_repetition(*turn) && previous_boards.contains(board.key) {
+            return Err(PlayError::RepeatedPosition);
         }
 
         let mut captures = Vec::new();
-        board.captures(&play.to, role_from, &mut captures);
-        board.captures_shield_wall(role_from, &play.to, &mut captures);
+        board.captures(&play.to, role_from, rules, &mut captures);
+        if rules.shield_walls {
+            board.captures_shield_wall(role_from, &play.to, rules, &mut captures);
+        }
 
-        if EXIT_SQUARES.contains(&play.to) {
-            return Ok((board, captures, Status::DefenderWins));
+        if space_from == Space::King && rules.exits(self.size).contains(&play.to) {
+            return Ok((board, captures, Status::DefenderWins, Some(GameEnd::Escape)));
         }
 
-        if board.capture_the_king(&play.to, &mut captures)? {
-            return Ok((board, captures, Status::AttackerWins));
+        if board.capture_the_king(&play.to, rules, &mut captures) {
+            return Ok((
+                board,
+                captures,
+                Status::AttackerWins,
+                Some(GameEnd::KingCaptured),
+            ));
         }
 
-        if board.exit_forts()? {
-            return Ok((board, captures, Status::DefenderWins));
+        if rules.exit_forts && board.exit_forts() {
+            return Ok((
+                board,
+                captures,
+                Status::DefenderWins,
+                Some(GameEnd::ExitFort),
+            ));
         }
-        if board.flood_fill_attacker_wins()? {
-            return Ok((board, captures, Status::AttackerWins));
+        if rules.encirclement && board.flood_fill_attacker_wins() {
+            return Ok((
+                board,
+                captures,
+                Status::AttackerWins,
+                Some(GameEnd::Encirclement),
+            ));
         }
 
-        if board.no_attacker_pieces_left() {
-            return Ok((board, captures, Status::DefenderWins));
+        if board.attackers.is_empty() {
+            return Ok((
+                board,
+                captures,
+                Status::DefenderWins,
+                Some(GameEnd::AllCaptured),
+            ));
         }
 
-        // Todo: Is a draw possible, how?
+        // Draws depend on the plays before this one, so `Game::play` declares them.
 
-        Ok((board, captures, Status::Ongoing))
+        Ok((board, captures, Status::Ongoing, None))
+    }
+
+    /// The board after `play`, before anything is captured.
+    #[must_use]
+    fn moved(&self, play: &Play) -> Board {
+        let mut board = self.clone();
+        board.set(&play.to, self.get(&play.from));
+        board.set(&play.from, Space::Empty);
+        board
+    }
+
+    /// Whether the legal `play` captures a piece between two hostile squares. Shield walls are
+    /// left out, they are rare and slow to find.
+    #[must_use]
+    pub fn captures_a_piece(&self, play: &Play, rules: &RuleSet) -> bool {
+        let mut board = self.moved(play);
+        let mut captures = Vec::new();
+        board.captures(&play.to, self.get(&play.from).role(), rules, &mut captures);
+
+        !captures.is_empty()
+    }
+
+    /// Whether `play` moves the pieces to a position that was already reached.
+    #[must_use]
+    fn repeats(&self, play: &Play, previous_boards: &PreviousBoards) -> bool {
+        let space = self.get(&play.from);
+        let key = self.key ^ ZOBRIST.piece(&play.from, space) ^ ZOBRIST.piece(&play.to, space);
+
+        previous_boards.contains(key)
     }
 
     fn set(&mut self, vertex: &Vertex, space: Space) {
-        self.spaces[vertex.y * 11 + vertex.x] = space;
+        let old_space = self.get(vertex);
+        if old_space != Space::Empty {
+            self.key ^= ZOBRIST.piece(vertex, old_space);
+        }
+        if space != Space::Empty {
+            self.key ^= ZOBRIST.piece(vertex, space);
+        }
+
+        self.attackers.remove(vertex);
+        self.defenders.remove(vertex);
+        self.king.remove(vertex);
+
+        match space {
+            Space::Attacker => self.attackers.insert(vertex),
+            Space::Defender => self.defenders.insert(vertex),
+            Space::King => self.king.insert(vertex),
+            Space::Empty => {}
+        }
     }
 
     #[must_use]
     fn set_if_not_king(&mut self, vertex: &Vertex, space: Space) -> bool {
-        if self.get(vertex) == Space::King {
+        if self.king.contains(vertex) {
             false
         } else {
             self.set(vertex, space);
@@ -929,8 +1349,8 @@ impl Board {
 }
 
 pub struct Captured {
-    attacker: u8,
-    defender: u8,
+    attacker: u16,
+    defender: u16,
     king: bool,
 }
 
@@ -961,21 +1381,3 @@ pub struct LegalMoves {
     pub role: Role,
     pub moves: HashMap<Vertex, Vec<Vertex>>,
 }
-
-#[must_use]
-fn expand_flood_fill(
-    vertex: Option<Vertex>,
-    already_checked: &mut FxHashSet<Vertex>,
-    stack: &mut Vec<Vertex>,
-) -> bool {
-    if let Some(vertex) = vertex {
-        if !already_checked.contains(&vertex) {
-            stack.push(vertex.clone());
-            already_checked.insert(vertex);
-        }
-
-        true
-    } else {
-        false
-    }
-}
@@ -0,0 +1,74 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::bitboard::{MASKS, Masks};
+
+/// How many squares there are along each side of the board.
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+pub enum BoardSize {
+    _7,
+    _9,
+    #[default]
+    _11,
+    _13,
+    _15,
+    _17,
+    _19,
+}
+
+impl BoardSize {
+    #[must_use]
+    pub const fn get(self) -> usize {
+        match self {
+            Self::_7 => 7,
+            Self::_9 => 9,
+            Self::_11 => 11,
+            Self::_13 => 13,
+            Self::_15 => 15,
+            Self::_17 => 17,
+            Self::_19 => 19,
+        }
+    }
+
+    /// The throne, corners, and edges of a board this size.
+    #[must_use]
+    pub fn masks(self) -> &'static Masks {
+        &MASKS[self as usize]
+    }
+}
+
+impl fmt::Display for BoardSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get())
+    }
+}
+
+impl FromStr for BoardSize {
+    type Err = anyhow::Error;
+
+    fn from_str(size: &str) -> anyhow::Result<Self> {
+        Self::try_from(size.parse::<usize>()?)
+    }
+}
+
+impl TryFrom<usize> for BoardSize {
+    type Error = anyhow::Error;
+
+    fn try_from(size: usize) -> anyhow::Result<Self> {
+        match size {
+            7 => Ok(Self::_7),
+            9 => Ok(Self::_9),
+            11 => Ok(Self::_11),
+            13 => Ok(Self::_13),
+            15 => Ok(Self::_15),
+            17 => Ok(Self::_17),
+            19 => Ok(Self::_19),
+            _ => Err(anyhow::Error::msg(format!(
+                "the board size has to be 7, 9, 11, 13, 15, 17, or 19, not {size}"
+            ))),
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    fmt::{self, Write},
+    mem,
+};
+
+use crate::{
+    ai::AI,
+    game::Game,
+    glicko::{Outcome, Rating},
+    play::Plae,
+    position::Position,
+    role::Role,
+    status::Status,
+};
+
+/// How many plays a match game goes before it is scored as a draw, if it isn't told.
+pub const DEFAULT_MAX_PLAYS: usize = 500;
+
+/// The results of a match, for the first AI.
+#[derive(Clone, Debug, Default)]
+pub struct Score {
+    pub outcomes: Vec<Outcome>,
+}
+
+impl Score {
+    #[must_use]
+    pub fn wins(&self) -> usize {
+        self.count(&Outcome::Win)
+    }
+
+    #[must_use]
+    pub fn draws(&self) -> usize {
+        self.count(&Outcome::Draw)
+    }
+
+    #[must_use]
+    pub fn losses(&self) -> usize {
+        self.count(&Outcome::Loss)
+    }
+
+    /// A point for a win and half a point for a draw.
+    #[must_use]
+    pub fn points(&self) -> f64 {
+        self.outcomes.iter().map(Outcome::score).sum()
+    }
+
+    /// How many Elo points stronger the first AI is, if it didn't win or lose every game.
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn elo_difference(&self) -> Option<f64> {
+        let share = self.points() / self.outcomes.len() as f64;
+
+        if share > 0.0 && share < 1.0 {
+            Some(400.0 * (share / (1.0 - share)).log10())
+        } else {
+            None
+        }
+    }
+
+    /// The Glicko rating of the first AI, starting from the default rating and playing each
+    /// game against an opponent with the default rating.
+    #[must_use]
+    pub fn rating(&self) -> Rating {
+        let opponent = Rating::default();
+        let mut rating = Rating::default();
+
+        for outcome in &self.outcomes {
+            rating.update_rating(opponent.rating, outcome);
+        }
+
+        rating
+    }
+
+    fn count(&self, outcome: &Outcome) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome_2| mem::discriminant(*outcome_2) == mem::discriminant(outcome))
+            .count()
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{} ={} -{}", self.wins(), self.draws(), self.losses())
+    }
+}
+
+/// How the game went for `role`, a game that didn't end is a draw.
+#[must_use]
+pub fn outcome(game: &Game, role: Role) -> Outcome {
+    match (&game.status, role) {
+        (Status::AttackerWins, Role::Attacker) | (Status::DefenderWins, Role::Defender) => {
+            Outcome::Win
+        }
+        (Status::AttackerWins, Role::Defender) | (Status::DefenderWins, Role::Attacker) => {
+            Outcome::Loss
+        }
+        _ => Outcome::Draw,
+    }
+}
+
+/// Plays the game out between the AIs, for at most `max_plays` plays. An AI that has no play or
+/// makes an illegal play resigns.
+pub fn play_game(game: &mut Game, attacker: &mut dyn AI, defender: &mut dyn AI, max_plays: usize) {
+    for _ in 0..max_plays {
+        if game.status != Status::Ongoing {
+            return;
+        }
+
+        let (play, resigns) = match game.turn {
+            Role::Attacker => (attacker.generate_move(game), Plae::AttackerResigns),
+            Role::Defender | Role::Roleless => {
+                (defender.generate_move(game), Plae::DefenderResigns)
+            }
+        };
+
+        let play = play.unwrap_or(resigns.clone());
+        if game.play(&play).is_err() {
+            // Resigning on your own turn is always legal.
+            let _ = game.play(&resigns);
+        }
+    }
+}
+
+/// A record of the game in the spirit of PGN: tag lines, then the numbered plays.
+#[must_use]
+pub fn game_log(tags: &[(&str, String)], start: &Position, game: &Game) -> String {
+    let mut log = String::new();
+
+    for (name, value) in tags {
+        let _ = writeln!(log, "[{name} \"{value}\"]");
+    }
+    let _ = writeln!(log, "[Position \"{start}\"]");
+    let _ = writeln!(log, "[Result \"{}\"]", game.status);
+    if let Some(game_end) = &game.game_end {
+        let _ = writeln!(log, "[GameEnd \"{game_end}\"]");
+    }
+    log.push('\n');
+
+    let mut move_number = start.move_number;
+    let mut turn = start.turn;
+    let plays = game
+        .plays
+        .0
+        .iter()
+        .filter_map(|record| record.play.as_ref());
+
+    for (index, play) in plays.enumerate() {
+        match turn {
+            Role::Attacker => {
+                let _ = write!(log, "{move_number}. ");
+            }
+            Role::Defender | Role::Roleless => {
+                if index == 0 {
+                    let _ = write!(log, "{move_number}... ");
+                }
+            }
+        }
+
+        match play {
+            Plae::Play(play) => {
+                let _ = write!(log, "{}-{} ", play.from, play.to);
+            }
+            Plae::AttackerResigns | Plae::DefenderResigns => log.push_str("resigns "),
+        }
+
+        if turn == Role::Defender {
+            move_number += 1;
+        }
+        turn = turn.opposite();
+    }
+
+    log.push_str(&game.status.to_string());
+    log.push_str("\n\n");
+    log
+}
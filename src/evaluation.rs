@@ -0,0 +1,183 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitboard::BitBoard, board::Board, game::Game, play::Vertex, role::Role, rules::RuleSet,
+    status::Status,
+};
+
+/// What `Evaluator` looks at in a position, each feature is counted as it stands.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Features {
+    pub attackers: i32,
+    /// The defenders, not counting the king.
+    pub defenders: i32,
+    /// How many squares the king is from the nearest exit that isn't walled in by attackers.
+    pub king_corner_distance: i32,
+    /// The exits the king can reach in one play.
+    pub king_escape_routes: i32,
+    pub king_attackers: i32,
+    /// The squares the defenders can play to, less the squares the attackers can play to.
+    pub mobility: i32,
+    /// The attackers next to a corner, less the defenders next to a corner.
+    pub corner_control: i32,
+    /// The defenders next to the king when it is on the edge, which may become an exit fort.
+    pub edge_fort: i32,
+}
+
+impl Features {
+    #[must_use]
+    pub fn new(game: &Game) -> Self {
+        let board = &game.board;
+        let rules = &game.rules;
+        let size = board.size;
+
+        let mut features = Self {
+            attackers: i32::from(board.attackers.count()),
+            defenders: i32::from(board.defenders.count()),
+            mobility: mobility(board, Role::Defender, rules)
+                - mobility(board, Role::Attacker, rules),
+            ..Self::default()
+        };
+
+        let corner_neighbors = size.masks().corners.neighbors(size);
+        features.corner_control = i32::from((corner_neighbors & board.attackers).count())
+            - i32::from((corner_neighbors & board.defenders).count());
+
+        let Some(king) = board.king.first(size) else {
+            return features;
+        };
+        let exits = rules.exits(size);
+
+        let open_exits = exits
+            .vertexes(size)
+            .filter(|exit| {
+                let neighbors = BitBoard::square(exit).neighbors(size);
+                neighbors & board.attackers != neighbors
+            })
+            .map(|exit| distance(&king, &exit))
+            .min();
+        // With every exit walled in the king is as far from escaping as it can be.
+        let distance = open_exits.unwrap_or(2 * (size.get() - 1));
+        features.king_corner_distance = i32::try_from(distance).unwrap_or_default();
+
+        features.king_escape_routes = i32::from((board.destinations(&king, rules) & exits).count());
+
+        let king_neighbors = board.king.neighbors(size);
+        features.king_attackers = i32::from((king_neighbors & board.attackers).count());
+
+        if size.masks().edges.contains(&king) {
+            features.edge_fort = i32::from((king_neighbors & board.defenders).count());
+        }
+
+        features
+    }
+}
+
+/// Scores positions for the AIs by the weighted sum of their `Features`. A higher score is
+/// better for the defender.
+///
+/// The weights can be loaded from a RON file. Each weight has the name of its feature, a name that
+/// isn't one is an error, and the weights left out keep their default:
+///
+/// ```ron
+/// (
+///     attackers: -10,
+///     defenders: 20,
+///     king_escape_routes: 1000,
+/// )
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Evaluator {
+    pub attackers: i32,
+    pub defenders: i32,
+    pub king_corner_distance: i32,
+    pub king_escape_routes: i32,
+    pub king_attackers: i32,
+    pub mobility: i32,
+    pub corner_control: i32,
+    pub edge_fort: i32,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self {
+            attackers: -10,
+            defenders: 20,
+            king_corner_distance: -4,
+            king_escape_routes: 1_000,
+            king_attackers: -15,
+            mobility: 1,
+            corner_control: -10,
+            edge_fort: 5,
+        }
+    }
+}
+
+impl Evaluator {
+    /// Loads the weights from a RON file.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read or isn't an `Evaluator`.
+    pub fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let string = fs::read_to_string(path)?;
+        Ok(ron::from_str(&string)?)
+    }
+
+    /// The score of the position, `i32::MAX` if the defender won and `i32::MIN` if the attacker
+    /// won.
+    #[must_use]
+    pub fn evaluate(&self, game: &Game) -> i32 {
+        match game.status {
+            Status::Ongoing => self.score(&Features::new(game)),
+            Status::AttackerWins => i32::MIN,
+            Status::Draw => 0,
+            Status::DefenderWins => i32::MAX,
+        }
+    }
+
+    /// Every weight, in the order of the fields.
+    pub fn weights_mut(&mut self) -> [&mut i32; 8] {
+        [
+            &mut self.attackers,
+            &mut self.defenders,
+            &mut self.king_corner_distance,
+            &mut self.king_escape_routes,
+            &mut self.king_attackers,
+            &mut self.mobility,
+            &mut self.corner_control,
+            &mut self.edge_fort,
+        ]
+    }
+
+    /// The weighted sum of the features, kept away from the scores of a won game.
+    #[must_use]
+    pub fn score(&self, features: &Features) -> i32 {
+        let score = i64::from(self.attackers) * i64::from(features.attackers)
+            + i64::from(self.defenders) * i64::from(features.defenders)
+            + i64::from(self.king_corner_distance) * i64::from(features.king_corner_distance)
+            + i64::from(self.king_escape_routes) * i64::from(features.king_escape_routes)
+            + i64::from(self.king_attackers) * i64::from(features.king_attackers)
+            + i64::from(self.mobility) * i64::from(features.mobility)
+            + i64::from(self.corner_control) * i64::from(features.corner_control)
+            + i64::from(self.edge_fort) * i64::from(features.edge_fort);
+
+        i32::try_from(score.clamp(i64::from(i32::MIN + 1), i64::from(i32::MAX - 1)))
+            .unwrap_or_default()
+    }
+}
+
+fn distance(a: &Vertex, b: &Vertex) -> usize {
+    a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+}
+
+fn mobility(board: &Board, role: Role, rules: &RuleSet) -> i32 {
+    board
+        .pieces(role)
+        .vertexes(board.size)
+        .map(|from| i32::from(board.destinations(&from, rules).count()))
+        .sum()
+}
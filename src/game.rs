@@ -1,35 +1,70 @@
-use std::{borrow::Cow, collections::HashMap, fmt, process::exit, str::FromStr};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    fmt,
+    process::exit,
+    sync::mpsc::Sender,
+};
 
+use anyhow::Context;
 use chrono::Local;
-use rustc_hash::FxHashSet;
+use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "js")]
 use wasm_bindgen::prelude::wasm_bindgen;
 
 use crate::{
-    ai::{AI, AiBanal},
+    ai::{AI, AiKind, AiSettings, SearchInfo},
     board::Board,
+    game_end::GameEnd,
     message::{COMMANDS, Message},
     play::{Captures, Plae, Play, Plays, Vertex},
+    play_error::PlayError,
+    position::Position,
     role::Role,
-    space::Space,
+    rules::RuleSet,
+    solver,
     status::Status,
     time::TimeSettings,
 };
 
+/// How many plays `Game::undo` can take back, older plays are forgotten so a long game doesn't
+/// keep every board it went through.
+pub const UNDO_LIMIT: usize = 512;
+
 #[cfg(not(feature = "js"))]
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Game {
+    /// The AI `generate_move` uses, chosen by the `ai` command.
     #[serde(skip)]
-    pub ai: AiBanal,
+    pub ai: AiSettings,
     pub board: Board,
     pub plays: Plays,
+    /// The plays made before the position the game started from.
+    #[serde(default)]
+    pub plays_before: usize,
+    /// The board the game started from, `reset_board` and `set_position` set it.
+    #[serde(default)]
+    pub start: Board,
     pub previous_boards: PreviousBoards,
     pub status: Status,
+    /// How the game ended, if it is over.
+    #[serde(default)]
+    pub game_end: Option<GameEnd>,
     pub time: TimeUnix,
     pub attacker_time: TimeSettings,
     pub defender_time: TimeSettings,
     pub turn: Role,
+    pub rules: RuleSet,
+    /// The plays in a row without a capture, for `RuleSet::move_limit`.
+    #[serde(default)]
+    pub plays_without_capture: u32,
+    /// The last `UNDO_LIMIT` plays, so `undo` can take them back.
+    #[serde(skip)]
+    pub undo_stack: VecDeque<Undo>,
+    /// Where `generate_move` and `analyze` send what the search found at each depth.
+    #[serde(skip)]
+    pub info: Option<Sender<SearchInfo>>,
 }
 
 #[cfg(feature = "js")]
@@ -37,17 +72,30 @@ pub struct Game {
 #[allow(clippy::unsafe_derive_deserialize)]
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Game {
+    /// The AI `generate_move` uses, chosen by the `ai` command.
     #[serde(skip)]
     #[wasm_bindgen(skip)]
-    pub ai: AiBanal,
+    pub ai: AiSettings,
     #[wasm_bindgen(skip)]
     pub board: Board,
     #[wasm_bindgen(skip)]
     pub plays: Plays,
+    /// The plays made before the position the game started from.
+    #[serde(default)]
+    #[wasm_bindgen(skip)]
+    pub plays_before: usize,
+    /// The board the game started from, `reset_board` and `set_position` set it.
+    #[serde(default)]
+    #[wasm_bindgen(skip)]
+    pub start: Board,
     #[wasm_bindgen(skip)]
     pub previous_boards: PreviousBoards,
     #[wasm_bindgen(skip)]
     pub status: Status,
+    /// How the game ended, if it is over.
+    #[serde(default)]
+    #[wasm_bindgen(skip)]
+    pub game_end: Option<GameEnd>,
     #[wasm_bindgen(skip)]
     pub time: TimeUnix,
     #[wasm_bindgen(skip)]
@@ -56,17 +104,63 @@ pub struct Game {
     pub defender_time: TimeSettings,
     #[wasm_bindgen(skip)]
     pub turn: Role,
+    #[wasm_bindgen(skip)]
+    pub rules: RuleSet,
+    /// The plays in a row without a capture, for `RuleSet::move_limit`.
+    #[serde(default)]
+    #[wasm_bindgen(skip)]
+    pub plays_without_capture: u32,
+    /// The last `UNDO_LIMIT` plays, so `undo` can take them back.
+    #[serde(skip)]
+    #[wasm_bindgen(skip)]
+    pub undo_stack: VecDeque<Undo>,
+    /// Where `generate_move` and `analyze` send what the search found at each depth.
+    #[serde(skip)]
+    #[wasm_bindgen(skip)]
+    pub info: Option<Sender<SearchInfo>>,
 }
 
+/// How many times each position was reached, by the Zobrist key of the board.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-pub struct PreviousBoards(pub FxHashSet<Board>);
+pub struct PreviousBoards(pub FxHashMap<u64, u32>);
 
 impl Default for PreviousBoards {
     fn default() -> Self {
-        let mut boards = FxHashSet::default();
+        Self::new(&Board::default())
+    }
+}
+
+impl PreviousBoards {
+    /// The previous boards of a game starting from `board`.
+    #[must_use]
+    pub fn new(board: &Board) -> Self {
+        let mut boards = Self(FxHashMap::default());
 
-        boards.insert(Board::default());
-        Self(boards)
+        boards.insert(board.key);
+        boards
+    }
+
+    #[must_use]
+    pub fn contains(&self, key: u64) -> bool {
+        self.0.contains_key(&key)
+    }
+
+    #[must_use]
+    pub fn count(&self, key: u64) -> u32 {
+        self.0.get(&key).copied().unwrap_or_default()
+    }
+
+    pub fn insert(&mut self, key: u64) {
+        *self.0.entry(key).or_default() += 1;
+    }
+
+    pub fn remove(&mut self, key: u64) {
+        if let Some(count) = self.0.get_mut(&key) {
+            *count -= 1;
+            if *count == 0 {
+                self.0.remove(&key);
+            }
+        }
     }
 }
 
@@ -115,7 +209,7 @@ impl Game {
             buffer.to_mut().replace_range(comment_offset.., "");
         }
 
-        match Message::from_str(buffer.as_ref()) {
+        match Message::parse(buffer.as_ref(), self.board.size) {
             Ok(message) => match self.update(message) {
                 Ok(update) => {
                     if let Some(update) = update {
@@ -124,7 +218,7 @@ impl Game {
                         String::new()
                     }
                 }
-                Err(err) => format!("? {err}"),
+                Err(err) => format!("? {}", crate::play_error::error_message(&err)),
             },
             Err(err) => format!("? {err}"),
         }
@@ -134,47 +228,17 @@ impl Game {
 impl Game {
     #[must_use]
     pub fn all_legal_moves(&self) -> LegalMoves {
-        let mut possible_vertexes = Vec::new();
         let mut legal_moves = LegalMoves {
             role: self.turn,
             moves: HashMap::new(),
         };
 
-        for y in 0..11 {
-            for x in 0..11 {
-                let vertex = Vertex { x, y };
-                if self.board.get(&vertex).role() == self.turn {
-                    possible_vertexes.push(vertex);
-                }
-            }
-        }
-
-        for vertex_from in possible_vertexes {
-            let mut vertexes_to = Vec::new();
-
-            for y in 0..11 {
-                for x in 0..11 {
-                    let vertex_to = Vertex { x, y };
-                    let play = Play {
-                        role: self.turn,
-                        from: vertex_from.clone(),
-                        to: vertex_to.clone(),
-                    };
-
-                    if let Ok(_board_captures_status) = self.board.play_internal(
-                        &Plae::Play(play),
-                        &self.status,
-                        &self.turn,
-                        &self.previous_boards,
-                    ) {
-                        vertexes_to.push(vertex_to);
-                    }
-                }
-            }
-
-            if !vertexes_to.is_empty() {
-                legal_moves.moves.insert(vertex_from, vertexes_to);
-            }
+        for play in self.legal_moves() {
+            legal_moves
+                .moves
+                .entry(play.from)
+                .or_default()
+                .push(play.to);
         }
 
         legal_moves
@@ -183,55 +247,25 @@ impl Game {
     #[allow(clippy::missing_panics_doc)]
     #[must_use]
     pub fn all_legal_plays(&self) -> Vec<Plae> {
-        let moves = self.all_legal_moves();
+        let mut plays: Vec<_> = self.legal_moves().map(Plae::Play).collect();
 
-        if moves.moves.is_empty() {
-            match &moves.role {
+        if plays.is_empty() {
+            match &self.turn {
                 Role::Attacker => return vec![Plae::AttackerResigns],
                 Role::Defender => return vec![Plae::DefenderResigns],
                 Role::Roleless => return Vec::new(),
             }
         }
 
-        let mut plays = Vec::new();
-        for (from, tos) in moves.moves {
-            for to in tos {
-                plays.push(Plae::Play(Play {
-                    role: moves.role,
-                    from: from.clone(),
-                    to,
-                }));
-            }
-        }
-
         plays.sort();
         plays
     }
 
     #[must_use]
     pub fn exit_one(&self) -> bool {
-        let exit_1 = Vertex { x: 0, y: 0 };
-        let exit_2 = Vertex { x: 10, y: 0 };
-        let exit_3 = Vertex { x: 0, y: 10 };
-        let exit_4 = Vertex { x: 10, y: 10 };
-
-        let mut game = self.clone();
-        if let Ok(Some(king)) = self.board.find_the_king() {
-            for exit in [exit_1, exit_2, exit_3, exit_4] {
-                if game
-                    .play(&Plae::Play(Play {
-                        role: self.turn,
-                        from: king.clone(),
-                        to: exit,
-                    }))
-                    .is_ok()
-                {
-                    return true;
-                }
-            }
-        }
-
-        false
+        let exits = self.rules.exits(self.board.size);
+        self.legal_moves()
+            .any(|play| self.board.king.contains(&play.from) && exits.contains(&play.to))
     }
 
     #[must_use]
@@ -239,88 +273,202 @@ impl Game {
         ai.generate_move(self)
     }
 
+    /// Every legal move for the side to play, there are none once the game is over.
+    pub fn legal_moves(&self) -> impl Iterator<Item = Play> + '_ {
+        let turn = if self.status == Status::Ongoing {
+            self.turn
+        } else {
+            Role::Roleless
+        };
+
+        self.board
+            .legal_moves(turn, &self.previous_boards, &self.rules)
+    }
+
     /// # Errors
     ///
     /// If the game is already over or the move is illegal.
-    pub fn play(&mut self, play: &Plae) -> anyhow::Result<Captures> {
+    pub fn play(&mut self, play: &Plae) -> Result<Captures, PlayError> {
+        let (captures, undo) = self.make(play)?;
+        if self.undo_stack.len() == UNDO_LIMIT {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(undo);
+
+        Ok(captures)
+    }
+
+    /// Plays `play` and returns what is needed to take it back with `unmake`. If the play is
+    /// illegal the game is left as it was.
+    pub(crate) fn make(&mut self, play: &Plae) -> Result<(Captures, Undo), PlayError> {
+        self.make_internal(play, true)
+    }
+
+    /// Like `make`, but leaves the clocks alone, for searching through plays.
+    pub(crate) fn make_untimed(&mut self, play: &Plae) -> Result<(Captures, Undo), PlayError> {
+        self.make_internal(play, false)
+    }
+
+    fn make_internal(&mut self, play: &Plae, timed: bool) -> Result<(Captures, Undo), PlayError> {
+        let mut undo = Undo {
+            board: self.board.clone(),
+            board_inserted: false,
+            plays: self.plays.0.len(),
+            status: self.status.clone(),
+            game_end: self.game_end,
+            turn: self.turn,
+            attacker_time: self.attacker_time.clone(),
+            defender_time: self.defender_time.clone(),
+            time: self.time.clone(),
+            plays_without_capture: self.plays_without_capture,
+        };
+
+        match self.apply(play, timed) {
+            Ok((captures, board_inserted)) => {
+                undo.board_inserted = board_inserted;
+                Ok((captures, undo))
+            }
+            Err(error) => {
+                self.unmake(undo);
+                Err(error)
+            }
+        }
+    }
+
+    /// Restores the game to how it was before the `make` that returned `undo`.
+    pub(crate) fn unmake(&mut self, undo: Undo) {
+        if undo.board_inserted {
+            self.previous_boards.remove(self.board.key);
+        }
+
+        self.board = undo.board;
+        self.plays.0.truncate(undo.plays);
+        self.status = undo.status;
+        self.game_end = undo.game_end;
+        self.turn = undo.turn;
+        self.attacker_time = undo.attacker_time;
+        self.defender_time = undo.defender_time;
+        self.time = undo.time;
+        self.plays_without_capture = undo.plays_without_capture;
+    }
+
+    /// # Errors
+    ///
+    /// If there is no play to undo.
+    pub fn undo(&mut self) -> anyhow::Result<()> {
+        let undo = self
+            .undo_stack
+            .pop_back()
+            .context("undo: there is no play to undo")?;
+
+        self.unmake(undo);
+        Ok(())
+    }
+
+    /// Returns the captures and whether a board was added to the previous boards.
+    #[allow(clippy::too_many_lines)]
+    fn apply(&mut self, play: &Plae, timed: bool) -> Result<(Captures, bool), PlayError> {
         if self.status == Status::Ongoing {
-            if let (status, TimeSettings::Timed(timer), TimeUnix::Time(time)) = match self.turn {
-                Role::Attacker => (
-                    Status::DefenderWins,
-                    &mut self.attacker_time,
-                    &mut self.time,
-                ),
-                Role::Roleless => {
-                    unreachable!("It can't be no one's turn when the game is ongoing!")
-                }
-                Role::Defender => (
-                    Status::AttackerWins,
-                    &mut self.defender_time,
-                    &mut self.time,
-                ),
-            } {
-                let now = Local::now().to_utc().timestamp_millis();
-                timer.milliseconds_left -= now - *time;
-
-                if timer.milliseconds_left <= 0 {
-                    self.status = status;
-                    return Ok(Captures::default());
-                }
+            if timed {
+                if let (status, TimeSettings::Timed(timer), TimeUnix::Time(time)) = match self.turn
+                {
+                    Role::Attacker => (
+                        Status::DefenderWins,
+                        &mut self.attacker_time,
+                        &mut self.time,
+                    ),
+                    Role::Roleless => {
+                        unreachable!("It can't be no one's turn when the game is ongoing!")
+                    }
+                    Role::Defender => (
+                        Status::AttackerWins,
+                        &mut self.defender_time,
+                        &mut self.time,
+                    ),
+                } {
+                    let now = Local::now().to_utc().timestamp_millis();
+                    timer.milliseconds_left -= now - *time;
+
+                    if timer.milliseconds_left <= 0 {
+                        self.status = status;
+                        self.game_end = Some(GameEnd::Timeout);
+                        return Ok((Captures::default(), false));
+                    }
 
-                timer.milliseconds_left += timer.add_seconds * 1_000;
-                *time = Local::now().to_utc().timestamp_millis();
+                    timer.milliseconds_left += timer.add_seconds * 1_000;
+                    *time = Local::now().to_utc().timestamp_millis();
+                }
             }
 
             match play {
                 Plae::AttackerResigns => {
                     if self.turn == Role::Attacker {
                         self.status = Status::DefenderWins;
+                        self.game_end = Some(GameEnd::Resignation);
                         self.plays.0.push(crate::play::PlayRecord {
                             play: Some(play.clone()),
                             attacker_time: self.attacker_time.clone(),
                             defender_time: self.defender_time.clone(),
                         });
-                        Ok(Captures::default())
+                        Ok((Captures::default(), false))
                     } else {
-                        Err(anyhow::Error::msg("You can't resign for the other player."))
+                        Err(PlayError::ResignOtherPlayer)
                     }
                 }
                 Plae::DefenderResigns => {
                     if self.turn == Role::Defender {
                         self.status = Status::AttackerWins;
+                        self.game_end = Some(GameEnd::Resignation);
                         self.plays.0.push(crate::play::PlayRecord {
                             play: Some(play.clone()),
                             attacker_time: self.attacker_time.clone(),
                             defender_time: self.defender_time.clone(),
                         });
-                        Ok(Captures::default())
+                        Ok((Captures::default(), false))
                     } else {
-                        Err(anyhow::Error::msg("You can't resign for the other player."))
+                        Err(PlayError::ResignOtherPlayer)
                     }
                 }
                 Plae::Play(play) => {
                     let piece_role = self.board.get(&play.from).role();
                     if piece_role != play.role {
-                        return Err(anyhow::Error::msg(format!(
-                            "play: you are trying to move {piece_role}, but it's {}'s turn",
-                            play.role
-                        )));
+                        return Err(PlayError::WrongPiece {
+                            piece: piece_role,
+                            play: play.role,
+                        });
                     }
 
-                    let (captures, status) = self.board.play(
+                    let (board, captures, status, game_end) = self.board.play_internal(
                         &Plae::Play(play.clone()),
                         &self.status,
                         &self.turn,
-                        &mut self.previous_boards,
+                        &self.previous_boards,
+                        &self.rules,
                     )?;
 
+                    self.previous_boards.insert(board.key);
+                    self.board = board;
                     self.status = status;
+                    self.game_end = game_end;
                     self.plays.0.push(crate::play::PlayRecord {
                         play: Some(Plae::Play(play.clone())),
                         attacker_time: self.attacker_time.clone(),
                         defender_time: self.defender_time.clone(),
                     });
 
+                    if captures.is_empty() {
+                        self.plays_without_capture += 1;
+                    } else {
+                        self.plays_without_capture = 0;
+                    }
+
+                    if self.status == Status::Ongoing {
+                        if let Some(game_end) = self.draw() {
+                            self.status = Status::Draw;
+                            self.game_end = Some(game_end);
+                        }
+                    }
+
                     if self.status == Status::Ongoing {
                         self.turn = self.turn.opposite();
 
@@ -328,24 +476,50 @@ impl Game {
                             &self.status,
                             &self.turn,
                             &self.previous_boards,
+                            &self.rules,
                         ) {
                             match self.turn {
                                 Role::Attacker => self.status = Status::DefenderWins,
                                 Role::Roleless => {}
                                 Role::Defender => self.status = Status::AttackerWins,
                             }
+                            self.game_end = Some(GameEnd::NoLegalMoves);
                         }
                     }
 
                     let captures = Captures(captures);
-                    Ok(captures)
+                    Ok((captures, true))
                 }
             }
         } else {
-            Err(anyhow::Error::msg("play: the game is already over"))
+            Err(PlayError::GameOver)
         }
     }
 
+    /// Why the play just made by `self.turn` draws the game under `self.rules`, if it does.
+    fn draw(&self) -> Option<GameEnd> {
+        if let Some(times) = self.rules.repetition_draw {
+            if self.turn == Role::Attacker && self.previous_boards.count(self.board.key) >= times {
+                return Some(GameEnd::Repetition);
+            }
+        }
+
+        if let Some(plays) = self.rules.move_limit {
+            if self.plays_without_capture >= plays {
+                return Some(GameEnd::MoveLimit);
+            }
+        }
+
+        if self
+            .rules
+            .insufficient_material(self.board.attackers.count(), self.board.defenders.count())
+        {
+            return Some(GameEnd::InsufficientMaterial);
+        }
+
+        None
+    }
+
     /// # Errors
     ///
     /// If the command is illegal or invalid.
@@ -355,19 +529,40 @@ impl Game {
             buffer.to_mut().replace_range(comment_offset.., "");
         }
 
-        self.update(Message::from_str(buffer.as_ref())?)
+        self.update(Message::parse(buffer.as_ref(), self.board.size)?)
     }
 
     /// # Errors
     ///
     /// If the command is illegal or invalid.
+    #[allow(clippy::too_many_lines)]
     pub fn update(&mut self, message: Message) -> anyhow::Result<Option<String>> {
-        let mut ai: Box<dyn AI> = Box::new(AiBanal);
-
         match message {
+            Message::Ai(ai) => {
+                if let Some(ai) = ai {
+                    self.ai = ai;
+                    Ok(Some(String::new()))
+                } else {
+                    Ok(Some(self.ai.to_string()))
+                }
+            }
+            Message::Analyze(depth) => {
+                let mut ai = self.ai.build_basic(self.info.as_ref());
+                ai.solver_nodes = 0;
+                if let Some(depth) = depth {
+                    ai.depth = depth;
+                }
+                ai.generate_move(self);
+
+                let analysis: Vec<_> = ai.analysis.iter().map(ToString::to_string).collect();
+                Ok(Some(analysis.join("\n")))
+            }
             Message::Empty => Ok(None),
             Message::FinalStatus => Ok(Some(format!("{}", self.status))),
-            Message::GenerateMove => Ok(self.generate_move(&mut ai).map(|play| play.to_string())),
+            Message::GenerateMove => {
+                let mut ai: Box<dyn AI> = self.ai.build(self.info.as_ref());
+                Ok(self.generate_move(&mut ai).map(|play| play.to_string()))
+            }
             Message::KnownCommand(command) => {
                 if COMMANDS.contains(&command.as_str()) {
                     Ok(Some("true".to_string()))
@@ -384,7 +579,7 @@ impl Game {
                 let name = env!("CARGO_PKG_NAME");
                 Ok(Some(name.to_string()))
             }
-            Message::Play(play) => self.play(&play).map(|captures| Some(captures.to_string())),
+            Message::Play(play) => Ok(Some(self.play(&play)?.to_string())),
             Message::PlayFrom => {
                 let moves = self.all_legal_moves();
                 Ok(Some(format!(
@@ -422,11 +617,50 @@ impl Game {
             }
             Message::ProtocolVersion => Ok(Some("1-beta".to_string())),
             Message::Quit => exit(0),
-            Message::ResetBoard => {
-                *self = Game::default();
+            Message::ResetBoard(starting_position) => {
+                let board = starting_position.board();
+                *self = Game {
+                    previous_boards: PreviousBoards::new(&board),
+                    start: board.clone(),
+                    board,
+                    rules: self.rules.clone(),
+                    ai: self.ai.clone(),
+                    info: self.info.take(),
+                    ..Game::default()
+                };
+                Ok(Some(String::new()))
+            }
+            Message::Rules(rules) => {
+                if let Some(rules) = rules {
+                    self.rules = rules;
+                    Ok(Some(String::new()))
+                } else {
+                    Ok(Some(self.rules.to_string()))
+                }
+            }
+            Message::SetPosition(position) => {
+                *self = Game {
+                    rules: self.rules.clone(),
+                    ai: self.ai.clone(),
+                    info: self.info.take(),
+                    ..Game::from(position)
+                };
                 Ok(Some(String::new()))
             }
             Message::ShowBoard => Ok(Some(self.board.to_string())),
+            Message::ShowPosition => Ok(Some(Position::from(&*self).to_string())),
+            Message::Skill(skill) => {
+                if self.ai.kind != AiKind::Basic {
+                    self.ai = AiSettings {
+                        kind: AiKind::Basic,
+                        ..AiSettings::default()
+                    };
+                }
+                self.ai.skill = Some(skill);
+
+                Ok(Some(String::new()))
+            }
+            Message::Solve(nodes) => Ok(Some(solver::solve(self, nodes).to_string())),
             Message::TimeSettings(time_settings) => {
                 match time_settings {
                     TimeSettings::Timed(time) => {
@@ -443,6 +677,10 @@ impl Game {
 
                 Ok(Some(String::new()))
             }
+            Message::Undo => {
+                self.undo()?;
+                Ok(Some(String::new()))
+            }
             Message::Version => {
                 let version = env!("CARGO_PKG_VERSION");
                 Ok(Some(version.to_string()))
@@ -461,18 +699,8 @@ impl Game {
 
         let mut utility = 0;
 
-        let mut defender_left = 0;
-        let mut attacker_left = 0;
-        for space in self.board.spaces {
-            match space {
-                Space::Attacker => attacker_left += 1,
-                Space::Empty | Space::King => {}
-                Space::Defender => defender_left += 1,
-            }
-        }
-
-        utility += defender_left * 2;
-        utility -= attacker_left;
+        utility += i32::from(self.board.defenders.count()) * 2;
+        utility -= i32::from(self.board.attackers.count());
 
         if self.exit_one() {
             utility += 100;
@@ -488,6 +716,21 @@ pub struct LegalMoves {
     pub moves: HashMap<Vertex, Vec<Vertex>>,
 }
 
+/// Everything `Game::play` changes, so the play can be taken back.
+#[derive(Clone, Debug)]
+pub struct Undo {
+    board: Board,
+    board_inserted: bool,
+    plays: usize,
+    status: Status,
+    game_end: Option<GameEnd>,
+    turn: Role,
+    attacker_time: TimeSettings,
+    defender_time: TimeSettings,
+    time: TimeUnix,
+    plays_without_capture: u32,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum TimeUnix {
     Time(i64),
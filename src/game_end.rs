@@ -0,0 +1,73 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// How a game that is no longer ongoing ended, `Status` says who won.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum GameEnd {
+    /// The king reached an exit.
+    Escape,
+    /// The defenders built a fort on the edge around a king that can move.
+    ExitFort,
+    /// The attackers captured the king.
+    KingCaptured,
+    /// The attackers surrounded all of the defenders.
+    Encirclement,
+    /// The defenders captured all of the attackers.
+    AllCaptured,
+    /// The side to play had no legal moves.
+    NoLegalMoves,
+    /// The side to play ran out of time.
+    Timeout,
+    /// The side to play resigned.
+    Resignation,
+    /// The attackers repeated a position too many times.
+    Repetition,
+    /// There were too many plays in a row without a capture.
+    MoveLimit,
+    /// Too few attackers were left to capture a lone king.
+    InsufficientMaterial,
+    /// The players agreed to a draw.
+    Agreement,
+}
+
+impl fmt::Display for GameEnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Escape => write!(f, "escape"),
+            Self::ExitFort => write!(f, "exit_fort"),
+            Self::KingCaptured => write!(f, "king_captured"),
+            Self::Encirclement => write!(f, "encirclement"),
+            Self::AllCaptured => write!(f, "all_captured"),
+            Self::NoLegalMoves => write!(f, "no_legal_moves"),
+            Self::Timeout => write!(f, "timeout"),
+            Self::Resignation => write!(f, "resignation"),
+            Self::Repetition => write!(f, "repetition"),
+            Self::MoveLimit => write!(f, "move_limit"),
+            Self::InsufficientMaterial => write!(f, "insufficient_material"),
+            Self::Agreement => write!(f, "agreement"),
+        }
+    }
+}
+
+impl FromStr for GameEnd {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "escape" => Ok(Self::Escape),
+            "exit_fort" => Ok(Self::ExitFort),
+            "king_captured" => Ok(Self::KingCaptured),
+            "encirclement" => Ok(Self::Encirclement),
+            "all_captured" => Ok(Self::AllCaptured),
+            "no_legal_moves" => Ok(Self::NoLegalMoves),
+            "timeout" => Ok(Self::Timeout),
+            "resignation" => Ok(Self::Resignation),
+            "repetition" => Ok(Self::Repetition),
+            "move_limit" => Ok(Self::MoveLimit),
+            "insufficient_material" => Ok(Self::InsufficientMaterial),
+            "agreement" => Ok(Self::Agreement),
+            _ => Err(anyhow::Error::msg(format!("invalid game end: {value}"))),
+        }
+    }
+}
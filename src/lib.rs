@@ -12,24 +12,40 @@ use game::Game;
 use game_record::{Captures, game_records_from_path};
 use message::Message;
 use play::{Plae, Vertex};
+use play_error::PlayError;
 use status::Status;
 
 pub mod accounts;
 pub mod ai;
+pub mod bitboard;
 pub mod board;
+pub mod board_size;
 pub mod draw;
+pub mod engine_match;
+pub mod evaluation;
 pub mod game;
+pub mod game_end;
 pub mod game_record;
 pub mod glicko;
 pub mod message;
+pub mod opening_book;
 pub mod play;
+pub mod play_error;
+pub mod play_ordering;
+pub mod position;
+pub mod puzzle;
 pub mod rating;
 pub mod role;
+pub mod rules;
 pub mod server_game;
 pub mod smtp;
+pub mod solver;
 pub mod space;
 pub mod status;
 pub mod time;
+pub mod transposition_table;
+pub mod tuning;
+pub mod zobrist;
 
 pub static HOME: &str = "hnefatafl-copenhagen";
 pub static VERSION_ID: &str = "1503174b";
@@ -156,9 +172,7 @@ pub fn hnefatafl_rs() -> anyhow::Result<()> {
                 }
             }
             Err(error) => {
-                if error.to_string()
-                    == anyhow::Error::msg("play: you already reached that position").to_string()
-                {
+                if error.downcast_ref::<PlayError>() == Some(&PlayError::RepeatedPosition) {
                     // already_played += 1;
                 } else {
                     return Err(anyhow::Error::msg(error.to_string()));
@@ -193,16 +207,39 @@ pub fn write_command(command: &str, stream: &mut TcpStream) -> anyhow::Result<()
 
 #[cfg(test)]
 mod tests {
-    use std::{fmt, str::FromStr};
+    use std::{
+        fmt,
+        str::FromStr,
+        sync::{Arc, mpsc},
+        time::{Duration, Instant},
+    };
 
-    use crate::ai::{AI, AiBanal};
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::ai::{
+        AI, AiBanal, AiBasic, AiBook, AiKind, AiMcts, AiPonder, AiSettings, SearchInfo,
+    };
 
     use super::*;
-    use board::{Board, STARTING_POSITION};
+    use board::{Board, STARTING_POSITION, StartingPosition};
+    use board_size::BoardSize;
+    use engine_match::Score;
+    use evaluation::{Evaluator, Features};
     use game::Game;
-    use play::Vertex;
+    use game_end::GameEnd;
+    use glicko::Outcome;
+    use opening_book::{DEFAULT_PLAYS, OpeningBook};
+    use play::{Play, Vertex};
+    use play_ordering::PlayOrdering;
+    use position::Position;
+    use puzzle::{Puzzle, PuzzleFinder, Theme};
     use role::Role;
+    use solver::Solution;
     use status::Status;
+    use time::TimeSettings;
+    use transposition_table::{Bound, Entry, TranspositionTable};
+
+    const BRANDUBH_POSITION: &str = "3X3/3X3/3O3/XXOKOXX/3O3/3X3/3X3 a 1";
 
     fn assert_error_str<T: fmt::Debug>(result: anyhow::Result<T>, string: &str) {
         if let Err(error) = result {
@@ -233,7 +270,7 @@ mod tests {
         };
 
         let vertex = Vertex::from_str("f1")?;
-        assert!(game.board.flood_fill_defender_wins(&vertex)?);
+        assert!(game.board.flood_fill_defender_wins(&vertex));
 
         Ok(())
     }
@@ -261,7 +298,7 @@ mod tests {
         };
 
         let vertex = Vertex::from_str("f1")?;
-        assert!(!game.board.flood_fill_defender_wins(&vertex)?);
+        assert!(!game.board.flood_fill_defender_wins(&vertex));
 
         Ok(())
     }
@@ -276,6 +313,81 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn board_serialization() -> anyhow::Result<()> {
+        let board = Board::default();
+        let string = ron::ser::to_string(&board)?;
+        assert!(string.starts_with("(spaces:(Empty,Empty,Empty,Attacker,"));
+
+        let board_2: Board = ron::from_str(&string)?;
+        assert_eq!(board, board_2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn board_serialization_postcard() -> anyhow::Result<()> {
+        // The bytes from before boards came in other sizes, one variant index per square.
+        let board = Board::default();
+        let bytes: Vec<u8> = STARTING_POSITION
+            .concat()
+            .chars()
+            .map(|ch| match ch {
+                'X' => 1,
+                'K' => 2,
+                'O' => 3,
+                _ => 0,
+            })
+            .collect();
+
+        assert_eq!(postcard::to_allocvec(&board)?, bytes);
+        assert_eq!(postcard::from_bytes::<Board>(&bytes)?, board);
+
+        let board = StartingPosition::Brandubh.board();
+        let bytes = postcard::to_allocvec(&board)?;
+        assert_eq!(bytes.len(), 1 + 7 * 7);
+        assert_eq!(postcard::from_bytes::<Board>(&bytes)?, board);
+
+        Ok(())
+    }
+
+    #[test]
+    fn play_serialization_postcard() -> anyhow::Result<()> {
+        // The bytes from before vertexes had a board size: the role, then x and y of each vertex.
+        let play = Play {
+            role: Role::Defender,
+            from: Vertex::from_str("a4")?,
+            to: Vertex::from_str("d4")?,
+        };
+        let bytes = [1, 0, 7, 3, 7];
+
+        assert_eq!(postcard::to_allocvec(&play)?, bytes);
+        assert_eq!(postcard::from_bytes::<Play>(&bytes)?, play);
+        assert_eq!(
+            ron::to_string(&play)?,
+            "(role:Defender,from:(x:0,y:7),to:(x:3,y:7))"
+        );
+
+        // Plays on other boards keep their size.
+        for (size, from, to) in [(BoardSize::_7, "a1", "a7"), (BoardSize::_19, "s1", "s19")] {
+            let play = Play {
+                role: Role::Attacker,
+                from: Vertex::parse(from, size)?,
+                to: Vertex::parse(to, size)?,
+            };
+
+            let bytes = postcard::to_allocvec(&play)?;
+            let play_2 = postcard::from_bytes::<Play>(&bytes)?;
+            assert_eq!(play_2, play);
+            assert_eq!(play_2.to.to_string(), to.to_uppercase());
+            assert_eq!(ron::from_str::<Play>(&ron::to_string(&play)?)?, play);
+        }
+
+        assert!(postcard::from_bytes::<Vertex>(&[11, 0]).is_err());
+
+        Ok(())
+    }
+
     // Two
 
     #[test]
@@ -397,6 +509,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn move_through_the_throne() -> anyhow::Result<()> {
+        let board = [
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "O..........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+        ];
+
+        let game = game::Game {
+            board: board.try_into()?,
+            turn: Role::Defender,
+            ..Default::default()
+        };
+
+        let moves = game.all_legal_moves();
+        let tos = &moves.moves[&Vertex::from_str("a6")?];
+        assert!(tos.contains(&Vertex::from_str("k6")?));
+        assert!(!tos.contains(&Vertex::from_str("f6")?));
+        assert_eq!(tos.len(), 17);
+        assert_eq!(game.legal_moves().count(), 17);
+
+        Ok(())
+    }
+
     // Four
 
     #[test]
@@ -1640,6 +1784,7 @@ mod tests {
 
         game.read_line("play defender f10 f11")?;
         assert_eq!(game.status, Status::DefenderWins);
+        assert_eq!(game.game_end, Some(GameEnd::ExitFort));
 
         let board = [
             "...........",
@@ -1815,6 +1960,7 @@ mod tests {
 
         game.read_line("play attacker f8 f7")?;
         assert_eq!(game.status, Status::AttackerWins);
+        assert_eq!(game.game_end, Some(GameEnd::KingCaptured));
 
         Ok(())
     }
@@ -1900,6 +2046,7 @@ mod tests {
         assert_eq!(game.status, Status::Ongoing);
         game.read_line("play attacker c7 d7")?;
         assert_eq!(game.status, Status::AttackerWins);
+        assert_eq!(game.game_end, Some(GameEnd::Encirclement));
 
         Ok(())
     }
@@ -1948,6 +2095,70 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn undo() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        let start = game.clone();
+
+        game.read_line("play attacker d11 d9")?;
+        game.read_line("play defender f4 c4")?;
+        game.read_line("play attacker h1 h3")?;
+        game.read_line("play defender c4 c9")?;
+        let middle = game.clone();
+
+        game.read_line("play attacker e11 e9")?;
+        game.read_line("undo")?;
+        assert_eq!(game.board, middle.board);
+        assert_eq!(game.previous_boards, middle.previous_boards);
+        assert_eq!(game.plays, middle.plays);
+        assert_eq!(game.turn, Role::Attacker);
+
+        for _ in 0..4 {
+            game.read_line("undo")?;
+        }
+
+        assert_eq!(game.board, start.board);
+        assert_eq!(game.previous_boards, start.previous_boards);
+        assert_eq!(game.plays, start.plays);
+        assert_eq!(game.status, start.status);
+        assert_eq!(game.turn, start.turn);
+        assert_eq!(game.attacker_time, start.attacker_time);
+        assert_eq!(game.defender_time, start.defender_time);
+
+        let result = game.read_line("undo");
+        assert!(result.is_err());
+        assert_error_str(result, "undo: there is no play to undo");
+
+        Ok(())
+    }
+
+    #[test]
+    fn make_untimed_leaves_the_clocks() -> anyhow::Result<()> {
+        let mut game = Game {
+            attacker_time: TimeSettings::Timed(time::Time {
+                add_seconds: 10,
+                milliseconds_left: 1_000,
+            }),
+            time: game::TimeUnix::Time(0),
+            ..Game::default()
+        };
+        let start = game.clone();
+
+        let play = Plae::try_from(vec!["play", "attacker", "d11", "d9"])?;
+        let (_captures, undo) = game.make_untimed(&play)?;
+        assert_eq!(game.status, Status::Ongoing);
+        assert_eq!(game.turn, Role::Defender);
+        assert_eq!(game.attacker_time, start.attacker_time);
+
+        game.unmake(undo);
+        assert_eq!(game.board, start.board);
+
+        let (_captures, _undo) = game.make(&play)?;
+        assert_eq!(game.status, Status::DefenderWins);
+
+        Ok(())
+    }
+
     // Nine
 
     #[test]
@@ -2144,6 +2355,1487 @@ mod tests {
         Ok(())
     }
 
+    // Rules
+
+    #[test]
+    fn rules_command() -> anyhow::Result<()> {
+        let mut game = Game::default();
+
+        assert_eq!(
+            game.read_line("rules")?,
+            Some("copenhagen corner_escape armed_king king_captured_by_4 shield_walls exit_forts encirclement repetition_defender no_repetition_draw no_move_limit no_insufficient_material".to_string())
+        );
+
+        game.read_line("rules fetlar edge_escape")?;
+        game.read_line("reset_board")?;
+        let rules = game.read_line("rules")?;
+        assert_eq!(
+            rules,
+            Some("house edge_escape armed_king king_captured_by_4 no_shield_walls no_exit_forts no_encirclement repetition_neither no_repetition_draw no_move_limit no_insufficient_material".to_string())
+        );
+
+        let rules = rules.unwrap_or_default();
+        game.read_line(&format!("rules {rules}"))?;
+        assert_eq!(game.read_line("rules")?, Some(rules));
+
+        let result = game.read_line("rules fetlar flying_king");
+        assert!(result.is_err());
+        assert_error_str(result, "unknown rule: flying_king");
+
+        let result = game.read_line("rules house edge_escape");
+        assert!(result.is_err());
+        assert_error_str(result, "rules house: each of the 10 rules has to be given");
+
+        Ok(())
+    }
+
+    #[test]
+    fn rules_edge_escape() -> anyhow::Result<()> {
+        let board = [
+            "..X........",
+            "...........",
+            ".....K.....",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+        ];
+
+        let mut game_1 = game::Game {
+            board: board.try_into()?,
+            turn: Role::Defender,
+            ..Default::default()
+        };
+        let mut game_2 = game_1.clone();
+        game_2.read_line("rules copenhagen edge_escape")?;
+
+        game_1.read_line("play defender f9 f11")?;
+        assert_eq!(game_1.status, Status::Ongoing);
+        game_2.read_line("play defender f9 f11")?;
+        assert_eq!(game_2.status, Status::DefenderWins);
+
+        let mut game = game::Game {
+            board: board.try_into()?,
+            ..Default::default()
+        };
+        game.read_line("rules copenhagen edge_escape")?;
+        game.read_line("play attacker c11 a11")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn rules_king_captured_by_2() -> anyhow::Result<()> {
+        let board = [
+            "...........",
+            "...........",
+            "....XK.....",
+            "...........",
+            "......X....",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+        ];
+
+        let mut game_1 = game::Game {
+            board: board.try_into()?,
+            ..Default::default()
+        };
+        let mut game_2 = game_1.clone();
+        game_2.read_line("rules copenhagen king_captured_by_2")?;
+
+        game_1.read_line("play attacker g7 g9")?;
+        assert_eq!(game_1.status, Status::Ongoing);
+        game_2.read_line("play attacker g7 g9")?;
+        assert_eq!(game_2.status, Status::AttackerWins);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rules_unarmed_king() -> anyhow::Result<()> {
+        let board = [
+            "..X........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...K.......",
+            "...........",
+            "....XO.....",
+            "...........",
+            "...........",
+            "...........",
+        ];
+
+        let mut game_1 = game::Game {
+            board: board.try_into()?,
+            turn: Role::Defender,
+            ..Default::default()
+        };
+        let mut game_2 = game_1.clone();
+        game_2.read_line("rules copenhagen unarmed_king")?;
+
+        game_1.read_line("play defender d6 d4")?;
+        assert_eq!(game_1.board.attackers.count(), 1);
+        game_2.read_line("play defender d6 d4")?;
+        assert_eq!(game_2.board.attackers.count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rules_fetlar_repeat_moves() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        game.read_line("rules fetlar")?;
+
+        game.read_line("play attacker f2 f3")?;
+        game.read_line("play defender f4 g4")?;
+        game.read_line("play attacker f3 f2")?;
+        game.read_line("play defender g4 f4")?;
+
+        game.read_line("rules copenhagen repetition_both")?;
+        let result = game.read_line("play attacker f2 f3");
+        assert!(result.is_err());
+        assert_error_str(result, "play: you already reached that position");
+
+        Ok(())
+    }
+
+    #[test]
+    fn rules_repetition_draw() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        game.read_line("rules fetlar repetition_draw_2")?;
+
+        game.read_line("play attacker f2 f3")?;
+        game.read_line("play defender f4 g4")?;
+        game.read_line("play attacker f3 f2")?;
+        game.read_line("play defender g4 f4")?;
+        assert_eq!(game.status, Status::Ongoing);
+
+        game.read_line("play attacker f2 f3")?;
+        assert_eq!(game.status, Status::Draw);
+        assert_eq!(game.game_end, Some(GameEnd::Repetition));
+
+        let result = game.read_line("play defender f4 g4");
+        assert!(result.is_err());
+        assert_error_str(result, "play: the game is already over");
+
+        Ok(())
+    }
+
+    #[test]
+    fn rules_move_limit() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        game.read_line("rules copenhagen move_limit_4")?;
+
+        game.read_line("play attacker f2 f3")?;
+        game.read_line("play defender f4 g4")?;
+        game.read_line("play attacker f3 f2")?;
+        assert_eq!(game.plays_without_capture, 3);
+        game.read_line("play defender g4 g3")?;
+        assert_eq!(game.status, Status::Draw);
+        assert_eq!(game.game_end, Some(GameEnd::MoveLimit));
+
+        game.read_line("undo")?;
+        assert_eq!(game.status, Status::Ongoing);
+        assert_eq!(game.game_end, None);
+        assert_eq!(game.plays_without_capture, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rules_insufficient_material() -> anyhow::Result<()> {
+        let board = [
+            "..X........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...K.......",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            ".........X.",
+        ];
+
+        let mut game_1 = game::Game {
+            board: board.try_into()?,
+            ..Default::default()
+        };
+        let mut game_2 = game_1.clone();
+        game_2.read_line("rules copenhagen insufficient_material")?;
+
+        game_1.read_line("play attacker c11 c10")?;
+        assert_eq!(game_1.status, Status::Ongoing);
+        game_2.read_line("play attacker c11 c10")?;
+        assert_eq!(game_2.status, Status::Draw);
+        assert_eq!(game_2.game_end, Some(GameEnd::InsufficientMaterial));
+
+        Ok(())
+    }
+
+    // Game End
+
+    #[test]
+    fn game_end_escape_and_resignation() -> anyhow::Result<()> {
+        let board = [
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "X....K.....",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+        ];
+
+        let mut game = game::Game {
+            board: board.try_into()?,
+            turn: Role::Defender,
+            ..Default::default()
+        };
+
+        game.read_line("play defender f6 f11")?;
+        game.read_line("play attacker a6 b6")?;
+        game.read_line("play defender f11 k11")?;
+        assert_eq!(game.status, Status::DefenderWins);
+        assert_eq!(game.game_end, Some(GameEnd::Escape));
+
+        game.read_line("undo")?;
+        assert_eq!(game.game_end, None);
+
+        game.read_line("play defender resigns")?;
+        assert_eq!(game.status, Status::AttackerWins);
+        assert_eq!(game.game_end, Some(GameEnd::Resignation));
+
+        Ok(())
+    }
+
+    // Play Errors
+
+    #[test]
+    fn play_errors() -> anyhow::Result<()> {
+        let mut game = Game::default();
+
+        let result = game.play(&Plae::try_from(vec!["play", "defender", "f4", "g4"])?);
+        assert_eq!(result.err(), Some(PlayError::NotYourTurn));
+
+        let result = game.play(&Plae::try_from(vec!["play", "attacker", "f1", "f3"])?);
+        assert_eq!(result.err(), Some(PlayError::BlockedPath));
+
+        let result = game.play(&Plae::try_from(vec!["play", "attacker", "a4", "a1"])?);
+        assert_eq!(result.err(), Some(PlayError::RestrictedSquare));
+
+        let result = game.play(&Plae::try_from(vec!["play", "defender", "resigns"])?);
+        assert_eq!(result.err(), Some(PlayError::ResignOtherPlayer));
+
+        let Err(error) = game.read_line("play attacker a4 b5") else {
+            panic!("the play is not in a straight line");
+        };
+        assert_eq!(
+            play_error::error_message(&error),
+            "not_straight_line play: you can only play in a straight line"
+        );
+
+        let Err(error) = game.read_line("play attacker z4 a5") else {
+            panic!("the vertex is not on the board");
+        };
+        assert_eq!(
+            play_error::error_message(&error),
+            "play: the first letter is not a legal char"
+        );
+
+        game.read_line("play attacker resigns")?;
+        let result = game.play(&Plae::try_from(vec!["play", "defender", "f4", "g4"])?);
+        assert_eq!(result.err(), Some(PlayError::GameOver));
+
+        Ok(())
+    }
+
+    // Positions
+
+    #[test]
+    fn show_position() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        assert_eq!(
+            game.read_line("show_position")?,
+            Some(
+                "3XXXXX3/5X5/11/X4O4X/X3OOO3X/XX1OOKOO1XX/X3OOO3X/X4O4X/11/5X5/3XXXXX3 a 1"
+                    .to_string()
+            )
+        );
+
+        game.read_line("play attacker d1 d2")?;
+        let position = game.read_line("show_position")?.unwrap_or_default();
+        assert!(position.ends_with(" d 1"));
+
+        game.read_line("play defender f4 g4")?;
+        let position = game.read_line("show_position")?.unwrap_or_default();
+        assert!(position.ends_with(" a 2"));
+
+        let mut game_2 = Game::default();
+        game_2.read_line(&format!("set_position {position}"))?;
+        assert_eq!(game_2.board, game.board);
+        assert_eq!(game_2.read_line("show_position")?, Some(position));
+
+        Ok(())
+    }
+
+    #[test]
+    fn set_position() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        game.read_line("rules copenhagen king_captured_by_2")?;
+        game.read_line("set_position 7/7/3K3/7/3X3/7/1X5 a 12")?;
+
+        assert_eq!(game.board.size, BoardSize::_7);
+        assert_eq!(game.turn, Role::Attacker);
+        assert_eq!(game.plays_before, 22);
+        assert_eq!(game.rules.king_capture, rules::KingCapture::TwoSides);
+
+        game.read_line("play attacker b1 b5")?;
+        game.read_line("play defender d5 c5")?;
+        game.read_line("play attacker d3 c3")?;
+        game.read_line("play defender c5 d5")?;
+        assert_eq!(
+            game.read_line("show_position")?,
+            Some("7/7/1X1K3/7/2X4/7/7 a 14".to_string())
+        );
+
+        game.undo_stack.clear();
+        assert_eq!(
+            position::Position::start(&game).to_string(),
+            "7/7/3K3/7/3X3/7/1X5 a 12"
+        );
+
+        let position: position::Position = BRANDUBH_POSITION.parse()?;
+        assert_eq!(position.board, StartingPosition::Brandubh.board());
+        assert_eq!(position.to_string(), BRANDUBH_POSITION);
+
+        assert!(game.read_line("set_position 7/7/3K3/7/3X3/7/1X5").is_err());
+        assert!(
+            game.read_line("set_position 7/7/3K3/7/3X3/7/1X5 r")
+                .is_err()
+        );
+        assert!(game.read_line("set_position 7/7/3K3/7/3X3/7 a").is_err());
+        assert!(
+            game.read_line("set_position 7/7/3K3/7/3X3/8/1X5 a")
+                .is_err()
+        );
+        assert!(
+            game.read_line("set_position 7/7/3K3/7/3X3/7/1X5 a 0")
+                .is_err()
+        );
+        assert!(
+            game.read_line("set_position 99999999999999999999/7/3K3/7/3X3/7/1X5 a")
+                .is_err()
+        );
+        assert!(
+            "7/7/3K3/7/3X3/7/1X5X9 a"
+                .parse::<position::Position>()
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    // AI
+
+    #[test]
+    fn ai_basic_time_budget() {
+        let ai = AiBasic::default();
+        let mut game = Game {
+            attacker_time: TimeSettings::Timed(time::Time {
+                add_seconds: 10,
+                milliseconds_left: 60_000,
+            }),
+            time: game::TimeUnix::UnTimed,
+            ..Game::default()
+        };
+        assert_eq!(ai.time_budget(&game), Duration::from_millis(9_500));
+
+        game.attacker_time = TimeSettings::Timed(time::Time {
+            add_seconds: 10,
+            milliseconds_left: 1_000,
+        });
+        assert_eq!(ai.time_budget(&game), Duration::from_millis(500));
+
+        game.attacker_time = TimeSettings::Timed(time::Time {
+            add_seconds: 0,
+            milliseconds_left: 60 * 60_000,
+        });
+        assert_eq!(ai.time_budget(&game), Duration::from_secs(15));
+
+        game.attacker_time = TimeSettings::UnTimed;
+        assert_eq!(ai.time_budget(&game), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn ai_basic_escapes() -> anyhow::Result<()> {
+        let board = [
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...X.......",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            ".......X...",
+            ".K.........",
+        ];
+
+        let mut game = game::Game {
+            board: board.try_into()?,
+            turn: Role::Defender,
+            ..Default::default()
+        };
+
+        let mut ai: Box<dyn AI> = Box::new(AiBasic {
+            depth: 3,
+            time_to_move: 10,
+            transposition_table: TranspositionTable::new(1),
+            ..AiBasic::default()
+        });
+        let Some(play) = game.generate_move(&mut ai) else {
+            panic!("the defender can play");
+        };
+        game.play(&play)?;
+        assert_eq!(game.status, Status::DefenderWins);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ai_basic_out_of_time() {
+        let game = Game::default();
+        let mut ai: Box<dyn AI> = Box::new(AiBasic {
+            depth: 10,
+            time_to_move: 0,
+            transposition_table: TranspositionTable::new(1),
+            ..AiBasic::default()
+        });
+
+        let Some(play) = game.generate_move(&mut ai) else {
+            panic!("there is always a play at the start");
+        };
+        let mut game = game.clone();
+        assert!(game.play(&play).is_ok());
+    }
+
+    #[test]
+    fn ai_basic_search_stats() {
+        let game = Game::default();
+        let mut ai = AiBasic {
+            depth: 2,
+            time_to_move: 60,
+            transposition_table: TranspositionTable::new(1),
+            ..AiBasic::default()
+        };
+
+        assert!(ai.generate_move(&game).is_some());
+        assert_eq!(ai.stats.depth, 2);
+        assert!(ai.stats.nodes > 1);
+        assert!(ai.stats.cutoffs >= ai.stats.first_play_cutoffs);
+        assert!(ai.stats.branching_factor() > 1.0);
+    }
+
+    #[test]
+    fn ai_mcts_escapes() -> anyhow::Result<()> {
+        let board = [
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...X.......",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            ".......X...",
+            ".K.........",
+        ];
+
+        let mut game = game::Game {
+            board: board.try_into()?,
+            turn: Role::Defender,
+            ..Default::default()
+        };
+
+        let mut ai: Box<dyn AI> = Box::new(AiMcts {
+            iterations: 1_000,
+            rng: StdRng::seed_from_u64(0),
+            ..AiMcts::default()
+        });
+        let Some(play) = game.generate_move(&mut ai) else {
+            panic!("the defender can play");
+        };
+        game.play(&play)?;
+        assert_eq!(game.status, Status::DefenderWins);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ai_mcts_seeded() {
+        let game = Game::default();
+        let ai = AiMcts {
+            iterations: 200,
+            rng: StdRng::seed_from_u64(7),
+            ..AiMcts::default()
+        };
+
+        let play_1 = ai.clone().generate_move(&game);
+        let play_2 = ai.clone().generate_move(&game);
+        assert!(play_1.is_some());
+        assert_eq!(play_1, play_2);
+    }
+
+    #[test]
+    fn evaluation_features() -> anyhow::Result<()> {
+        let board = [
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "X..........",
+            "...OK......",
+        ];
+
+        let game = game::Game {
+            board: board.try_into()?,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Features::new(&game),
+            Features {
+                attackers: 1,
+                defenders: 1,
+                king_corner_distance: 4,
+                king_escape_routes: 1,
+                king_attackers: 0,
+                mobility: 28 - 18,
+                corner_control: 1,
+                edge_fort: 1,
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn evaluation_features_walled_in_exits() -> anyhow::Result<()> {
+        let board = [
+            ".X.......X.",
+            "X.........X",
+            "...........",
+            "...........",
+            "...........",
+            ".....K.....",
+            "...........",
+            "...........",
+            "...........",
+            "X.........X",
+            ".X.......X.",
+        ];
+
+        let game = game::Game {
+            board: board.try_into()?,
+            ..Default::default()
+        };
+
+        // The king can't reach an exit, which is worse than being far from one.
+        assert_eq!(Features::new(&game).king_corner_distance, 20);
+
+        Ok(())
+    }
+
+    #[test]
+    fn evaluator() -> anyhow::Result<()> {
+        let evaluator: Evaluator = ron::from_str("(mobility: 3, edge_fort: 0)")?;
+        assert_eq!(evaluator.mobility, 3);
+        assert_eq!(evaluator.edge_fort, 0);
+        assert_eq!(evaluator.defenders, Evaluator::default().defenders);
+
+        // The weights are named after the features, a misspelled weight isn't skipped.
+        let evaluator: Evaluator = ron::from_str("(attackers: -5, defenders: 7)")?;
+        assert_eq!((evaluator.attackers, evaluator.defenders), (-5, 7));
+        assert!(ron::from_str::<Evaluator>("(attacker: -5)").is_err());
+        assert!(ron::from_str::<Evaluator>("(attacker_count: -5)").is_err());
+
+        let mut game = Game::default();
+        assert_eq!(
+            evaluator.evaluate(&game),
+            evaluator.score(&Features::new(&game))
+        );
+
+        game.read_line("play attacker resigns")?;
+        assert_eq!(evaluator.evaluate(&game), i32::MAX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn tuning() -> anyhow::Result<()> {
+        let records = game_records_from_path(Path::new("tests/copenhagen.csv"))?;
+        let mut samples = Vec::new();
+
+        for record in records
+            .into_iter()
+            .filter(|record| record.status != Status::Ongoing)
+            .take(20)
+        {
+            let plays = record.plays.into_iter().map(|(play, _)| Plae::Play(play));
+            samples.extend(tuning::samples(plays, &record.status));
+        }
+        assert!(!samples.is_empty());
+        assert!(tuning::samples(Vec::new(), &Status::Ongoing).is_empty());
+
+        let evaluator = Evaluator::default();
+        let k = tuning::fit_k(&evaluator, &samples);
+        let error = tuning::error(&evaluator, &samples, k);
+
+        let (evaluator_2, error_2) = tuning::tune(&evaluator, &samples, k, 10);
+        assert!(error_2 < error);
+        assert_ne!(evaluator, evaluator_2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn play_ordering() -> anyhow::Result<()> {
+        let board = [
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...O.......",
+            ".OX........",
+            "......XK...",
+        ];
+
+        let game = game::Game {
+            board: board.try_into()?,
+            turn: Role::Defender,
+            ..Default::default()
+        };
+        let ordering = PlayOrdering::default();
+
+        let escape = Plae::from_str_("h1-k1", &Role::Defender, BoardSize::_11)?;
+        let capture = Plae::from_str_("d3-d2", &Role::Defender, BoardSize::_11)?;
+        let Plae::Play(capture_play) = &capture else {
+            unreachable!();
+        };
+        assert!(game.board.captures_a_piece(capture_play, &game.rules));
+
+        let mut plays = game.all_legal_plays();
+        ordering.order(&game, &mut plays, None, 0);
+        // The king escapes first, then the defender captures, then the king heads for a corner.
+        assert_eq!(plays[0], escape);
+        assert_eq!(plays[1], capture);
+        let Plae::Play(play) = &plays[2] else {
+            panic!("the defender can't resign");
+        };
+        assert_eq!(play.from, Vertex::parse("h1", BoardSize::_11)?);
+
+        // The table play goes before everything.
+        let table_play = plays[5].clone();
+        ordering.order(&game, &mut plays, Some(&table_play), 0);
+        assert_eq!(plays[0], table_play);
+
+        Ok(())
+    }
+
+    #[test]
+    fn transposition_table() -> anyhow::Result<()> {
+        let table = TranspositionTable::new(1);
+        assert!(table.len().is_power_of_two());
+
+        let entry = Entry {
+            key: 7,
+            depth: 2,
+            bound: Bound::Lower,
+            value: 100,
+            play: None,
+        };
+        table.insert(&entry);
+        assert_eq!(table.get(7), Some(entry));
+
+        // Same slot, different position.
+        assert_eq!(table.get(7 + table.len() as u64), None);
+
+        table.clear();
+        assert_eq!(table.get(7), None);
+
+        for (size, play) in [(BoardSize::_11, "k1-a11"), (BoardSize::_19, "s19-a19")] {
+            let entry = Entry {
+                key: 9,
+                depth: 40,
+                bound: Bound::Upper,
+                value: i32::MIN,
+                play: Some(Plae::from_str_(play, &Role::Defender, size)?),
+            };
+            table.insert(&entry);
+            assert_eq!(table.get(9), Some(entry));
+        }
+
+        let entry = Entry {
+            key: 9,
+            depth: 1,
+            bound: Bound::Exact,
+            value: -3,
+            play: Some(Plae::AttackerResigns),
+        };
+        table.insert(&entry);
+        assert_eq!(table.get(9), Some(entry));
+
+        Ok(())
+    }
+
+    #[test]
+    fn ai_basic_threads() -> anyhow::Result<()> {
+        let game = Game::default();
+        let ai = AiBasic {
+            depth: 2,
+            time_to_move: 60,
+            transposition_table: TranspositionTable::new(1),
+            ..AiBasic::default()
+        };
+
+        // One thread searches the same way every time.
+        let mut ai_1 = ai.clone();
+        let mut ai_2 = ai.clone();
+        let play = ai_1.generate_move(&game);
+        assert_eq!(play, ai_2.generate_move(&game));
+        assert_eq!(ai_1.stats, ai_2.stats);
+
+        let mut ai_4 = AiBasic { threads: 4, ..ai };
+        let Some(play) = ai_4.generate_move(&game) else {
+            panic!("there is always a play at the start");
+        };
+        assert_eq!(ai_4.stats.depth, 2);
+        assert!(ai_4.stats.nodes > ai_1.stats.nodes);
+        game.clone().play(&play)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn ai_basic_keeps_transposition_table() -> anyhow::Result<()> {
+        let board = [
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...X.......",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            ".......X...",
+            ".K.........",
+        ];
+
+        let game = game::Game {
+            board: board.try_into()?,
+            turn: Role::Defender,
+            ..Default::default()
+        };
+
+        let mut ai = AiBasic {
+            depth: 3,
+            time_to_move: 10,
+            transposition_table: TranspositionTable::new(1),
+            solver_nodes: 0,
+            ..AiBasic::default()
+        };
+
+        // The second search starts with what the first one stored.
+        for _ in 0..2 {
+            let Some(play) = ai.generate_move(&game) else {
+                panic!("the defender can play");
+            };
+            let mut game = game.clone();
+            game.play(&play)?;
+            assert_eq!(game.status, Status::DefenderWins);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn ai_basic_analysis() {
+        let game = Game::default();
+        let (sender, receiver) = mpsc::channel();
+        let mut ai = AiBasic {
+            depth: 2,
+            time_to_move: 60,
+            transposition_table: TranspositionTable::new(1),
+            info: Some(sender),
+            ..AiBasic::default()
+        };
+
+        let play = ai.generate_move(&game);
+        assert_eq!(ai.analysis.len(), 2);
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), ai.analysis);
+
+        for (depth, info) in (1..).zip(&ai.analysis) {
+            assert_eq!(info.depth, depth);
+            assert!(info.nodes > 0);
+            assert!(!info.pv.is_empty() && info.pv.len() as u64 <= depth);
+        }
+        assert_eq!(ai.analysis[1].pv.first(), play.as_ref());
+
+        let info = SearchInfo {
+            depth: 3,
+            nodes: 2_000,
+            time: Duration::from_millis(500),
+            score: -i32::MAX,
+            pv: vec![Plae::AttackerResigns],
+        };
+        assert_eq!(
+            info.to_string(),
+            "info depth 3 nodes 2000 nps 4000 time 500 score loss pv resigns"
+        );
+    }
+
+    #[test]
+    fn analyze_command() -> anyhow::Result<()> {
+        let mut game = Game::default();
+
+        let Some(analysis) = game.read_line("analyze 2")? else {
+            panic!("analyze replies");
+        };
+        let lines: Vec<_> = analysis.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("info depth 1 nodes "));
+        assert!(lines[1].starts_with("info depth 2 nodes "));
+
+        // The game is as it was.
+        assert_eq!(game.turn, Role::Attacker);
+        assert!(game.read_line("analyze junk").is_err());
+
+        // Without a depth the analysis searches as deep as the AI settings say.
+        game.read_line("ai basic depth=1 seed=1")?;
+        let Some(analysis) = game.read_line("analyze")? else {
+            panic!("analyze replies");
+        };
+        assert_eq!(analysis.lines().count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ai_ponder() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        let mut ai = AiPonder::new(Box::new(AiBasic {
+            depth: 2,
+            time_to_move: 60,
+            transposition_table: TranspositionTable::new(1),
+            ..AiBasic::default()
+        }));
+
+        let Some(play) = ai.generate_move(&game) else {
+            panic!("there is always a play at the start");
+        };
+        game.play(&play)?;
+        assert!(ai.is_pondering());
+
+        // The expected reply continues the pondering search.
+        let Some(reply) = ai.expected_reply() else {
+            panic!("the search expects a reply");
+        };
+        game.play(&reply)?;
+        let Some(play) = ai.generate_move(&game) else {
+            panic!("the attacker can play");
+        };
+        game.play(&play)?;
+
+        // Another reply throws it away.
+        let Some(reply) = ai.expected_reply() else {
+            panic!("the search expects a reply");
+        };
+        let Some(other_reply) = game
+            .all_legal_plays()
+            .into_iter()
+            .find(|play| *play != reply)
+        else {
+            panic!("the defender has more than one play");
+        };
+        game.play(&other_reply)?;
+        let Some(play) = ai.generate_move(&game) else {
+            panic!("the attacker can play");
+        };
+        game.play(&play)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn ai_ponder_time_to_move() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        let mut ai = AiSettings {
+            kind: AiKind::Basic,
+            depth: Some(30),
+            time_to_move: Some(1),
+            hash: Some(1),
+            ponder: true,
+            ..AiSettings::default()
+        }
+        .build(None);
+
+        let Some(play) = ai.generate_move(&game) else {
+            panic!("there is always a play at the start");
+        };
+        game.play(&play)?;
+        let Some(reply) = ai.expected_reply() else {
+            panic!("the search expects a reply");
+        };
+        game.play(&reply)?;
+
+        // The search that can't finish stops when the time to move is up, not after the default.
+        let start = Instant::now();
+        assert!(ai.generate_move(&game).is_some());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn ai_settings_seed_book() -> anyhow::Result<()> {
+        let size = BoardSize::default();
+        let mut book = OpeningBook::default();
+        for play in ["d1-d2", "d1-d3", "a4-a2", "a4-a3"] {
+            let play = Plae::from_str_(play, &Role::Attacker, size)?;
+            book.add_game([play], &Status::Draw, DEFAULT_PLAYS);
+        }
+
+        let ai_settings = AiSettings {
+            kind: AiKind::Basic,
+            seed: Some(7),
+            book: Some(Arc::new(book)),
+            ..AiSettings::default()
+        };
+
+        // The same seed picks the same book plays.
+        let game = Game::default();
+        let mut ai_1 = ai_settings.build(None);
+        let mut ai_2 = ai_settings.build(None);
+        for _ in 0..8 {
+            assert_eq!(ai_1.generate_move(&game), ai_2.generate_move(&game));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn ai_basic_skill() -> anyhow::Result<()> {
+        let weakest = AiBasic::with_skill(1);
+        assert_eq!(weakest.depth, 1);
+        assert!(weakest.noise > 0);
+        assert!(weakest.temperature > 0.0);
+
+        let strongest = AiBasic::with_skill(ai::MAX_SKILL);
+        assert_eq!(strongest.depth, AiBasic::default().depth);
+        assert_eq!(strongest.noise, 0);
+        assert!(strongest.temperature == 0.0);
+
+        let game = Game::default();
+        let ai = AiBasic {
+            transposition_table: TranspositionTable::new(1),
+            rng: StdRng::seed_from_u64(3),
+            ..AiBasic::with_skill(3)
+        };
+
+        // The same seed makes the same choices.
+        let mut ai_1 = ai.clone();
+        let mut ai_2 = ai.clone();
+        let play = ai_1.generate_move(&game);
+        assert_eq!(play, ai_2.generate_move(&game));
+
+        let Some(play) = play else {
+            panic!("there is always a play at the start");
+        };
+        game.clone().play(&play)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn skill_command() -> anyhow::Result<()> {
+        let mut game = Game::default();
+
+        assert!(game.read_line("skill 0").is_err());
+        assert!(game.read_line("skill 21").is_err());
+        assert!(game.read_line("skill").is_err());
+
+        game.read_line("skill 1")?;
+        assert_eq!(game.ai.kind, AiKind::Basic);
+        assert_eq!(game.ai.skill, Some(1));
+
+        let Some(play) = game.read_line("generate_move")? else {
+            panic!("there is always a play at the start");
+        };
+        game.read_line(&play)?;
+        assert_eq!(game.turn, Role::Defender);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ai_command() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        assert_eq!(game.read_line("ai")?, Some("banal".to_string()));
+
+        game.read_line("ai basic depth=2 time=5 seed=1")?;
+        assert_eq!(
+            game.read_line("ai")?,
+            Some("basic depth=2 time=5 seed=1".to_string())
+        );
+        assert_eq!(game.ai.depth, Some(2));
+
+        // The same seed makes the same plays.
+        let play = game.read_line("generate_move")?;
+        assert_eq!(game.read_line("generate_move")?, play);
+        let Some(play) = play else {
+            panic!("there is always a play at the start");
+        };
+        game.read_line(&play)?;
+
+        game.read_line("skill 3")?;
+        assert_eq!(
+            game.read_line("ai")?,
+            Some("basic depth=2 time=5 skill=3 seed=1".to_string())
+        );
+
+        // The choice outlasts a new game.
+        game.read_line("reset_board")?;
+        assert_eq!(game.ai.depth, Some(2));
+
+        game.read_line("ai mcts iterations=50 seed=2")?;
+        assert!(game.read_line("generate_move")?.is_some());
+        game.read_line("skill 4")?;
+        assert_eq!(game.read_line("ai")?, Some("basic skill=4".to_string()));
+
+        assert!(game.read_line("ai deep").is_err());
+        assert!(game.read_line("ai banal depth=2").is_err());
+        assert!(game.read_line("ai basic depth").is_err());
+        assert!(game.read_line("ai basic depth=x").is_err());
+        assert!(game.read_line("ai basic skill=21").is_err());
+        assert!(game.read_line("ai mcts depth=2").is_err());
+        assert!(game.read_line("ai mcts hash=64").is_err());
+        assert!(game.read_line("ai basic ponder=true").is_err());
+
+        game.read_line("ai basic hash=1")?;
+        assert_eq!(game.read_line("ai")?, Some("basic hash=1".to_string()));
+
+        // The options the programs set are checked against the AI too.
+        let ai = AiSettings {
+            kind: AiKind::Mcts,
+            threads: Some(2),
+            ..AiSettings::default()
+        };
+        assert!(ai.check().is_err());
+        let ai = AiSettings {
+            kind: AiKind::Basic,
+            threads: Some(2),
+            evaluator: Some(Evaluator::default()),
+            ponder: true,
+            ..AiSettings::default()
+        };
+        assert!(ai.check().is_ok());
+
+        // Another kind of AI keeps only the options it takes.
+        let mcts = ai.with_kind(AiKind::Mcts);
+        assert!(mcts.check().is_ok());
+        assert_eq!(mcts.threads, None);
+        assert!(mcts.evaluator.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn solver() -> anyhow::Result<()> {
+        let board = [
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...X.......",
+            "...........",
+            "...........",
+            "...........",
+            ".K.........",
+            ".......X...",
+            "...........",
+        ];
+
+        let game = game::Game {
+            board: board.try_into()?,
+            turn: Role::Defender,
+            ..Default::default()
+        };
+
+        // The king gets to an edge with two open corners, one play can't block both.
+        let Solution::Win(line) = solver::solve(&game, 100_000) else {
+            panic!("the defender wins");
+        };
+        assert_eq!(line.len(), 3);
+
+        let mut game_2 = game.clone();
+        for play in &line {
+            game_2.play(play)?;
+        }
+        assert_eq!(game_2.status, Status::DefenderWins);
+
+        assert!(solver::prove(&game, Role::Attacker, 10_000).is_none());
+        assert_eq!(solver::solve(&Game::default(), 1_000), Solution::Unknown);
+
+        Ok(())
+    }
+
+    #[test]
+    fn solve_command() -> anyhow::Result<()> {
+        let board = [
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...X.......",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            ".......X...",
+            ".K.........",
+        ];
+
+        let mut game = game::Game {
+            board: board.try_into()?,
+            turn: Role::Attacker,
+            ..Default::default()
+        };
+
+        let reply = game.read_line("solve 10000")?;
+        let Some(reply) = reply else {
+            panic!("solve replies");
+        };
+        assert!(reply.starts_with("loss "));
+        assert_eq!(reply.split_whitespace().count(), 3);
+
+        game.turn = Role::Defender;
+        assert_eq!(game.read_line("solve")?, Some("win B1-A1".to_string()));
+        assert!(game.read_line("solve junk").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn opening_book() -> anyhow::Result<()> {
+        let size = BoardSize::default();
+        let play = Plae::from_str_("d1-d2", &Role::Attacker, size)?;
+        let mirror_play = Plae::from_str_("h1-h2", &Role::Attacker, size)?;
+
+        let mut book = OpeningBook::default();
+        book.add_game([play.clone()], &Status::AttackerWins, DEFAULT_PLAYS);
+        book.add_game([mirror_play], &Status::DefenderWins, DEFAULT_PLAYS);
+        book.add_game([play.clone()], &Status::Ongoing, DEFAULT_PLAYS);
+
+        // The mirror image of a play is the same play.
+        let game = Game::default();
+        let plays = book.plays(&game);
+        assert_eq!(plays.len(), 1);
+        assert_eq!(plays[0].games, 2);
+        assert_eq!(plays[0].wins, 1);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let Some(book_play) = book.choose(&game, &mut rng) else {
+            panic!("the position is in the book");
+        };
+        game.clone().play(&book_play)?;
+
+        let mut game_2 = game.clone();
+        game_2.play(&play)?;
+        assert!(book.choose(&game_2, &mut rng).is_none());
+
+        let mut ai = AiBook::new(book.clone(), Box::new(AiBanal));
+        ai.rng = StdRng::seed_from_u64(0);
+        assert_eq!(ai.generate_move(&game), Some(book_play));
+        assert!(ai.generate_move(&game_2).is_some());
+
+        // After a book play the book says what the reply is, not the AI that searched last.
+        let reply = Plae::from_str_("f4-g4", &Role::Defender, size)?;
+        let mut book_2 = OpeningBook::default();
+        book_2.add_game([play, reply], &Status::Draw, DEFAULT_PLAYS);
+        let mut ai = AiBook::new(book_2, Box::new(AiBanal));
+        let Some(book_play) = ai.generate_move(&game) else {
+            panic!("the position is in the book");
+        };
+        let Some(book_reply) = ai.expected_reply() else {
+            panic!("the position after the play is in the book");
+        };
+        let mut game_3 = game.clone();
+        game_3.play(&book_play)?;
+        game_3.play(&book_reply)?;
+
+        let ron = ron::to_string(&book)?;
+        assert_eq!(ron::from_str::<OpeningBook>(&ron)?, book);
+        let bytes = postcard::to_allocvec(&book)?;
+        assert_eq!(postcard::from_bytes::<OpeningBook>(&bytes)?, book);
+
+        Ok(())
+    }
+
+    #[test]
+    fn engine_match_score() {
+        let mut score = Score::default();
+        assert!(score.elo_difference().is_none());
+
+        score.outcomes.push(Outcome::Win);
+        assert!(score.elo_difference().is_none());
+
+        score.outcomes.push(Outcome::Loss);
+        score.outcomes.push(Outcome::Draw);
+        assert_eq!(score.to_string(), "+1 =1 -1");
+        assert!(score.points() == 1.5);
+        assert_eq!(score.elo_difference().map(f64::round), Some(0.0));
+
+        score.outcomes.push(Outcome::Win);
+        let Some(elo) = score.elo_difference() else {
+            panic!("the score isn't all wins or all losses");
+        };
+        assert!(elo > 0.0);
+        assert!(score.rating().rating > 1_500.0);
+        assert!(score.rating().rd < 350.0);
+    }
+
+    #[test]
+    fn engine_match_play_game() -> anyhow::Result<()> {
+        let start = Position::from(&Game::default());
+        let mut game = Game::from(start.clone());
+        game.read_line("time_settings un-timed")?;
+
+        engine_match::play_game(&mut game, &mut AiBanal, &mut AiBanal, 5);
+        assert_eq!(game.plays.0.len(), 5);
+        assert_eq!(game.status, Status::Ongoing);
+        assert!(matches!(
+            engine_match::outcome(&game, Role::Attacker),
+            Outcome::Draw
+        ));
+
+        let tags = [("Game", "1".to_string())];
+        let log = engine_match::game_log(&tags, &start, &game);
+        assert!(log.starts_with("[Game \"1\"]\n[Position \""));
+        assert!(log.contains("\n1. "));
+        assert!(log.contains(" 3. "));
+        assert!(log.ends_with("ongoing\n\n"));
+
+        game.play(&Plae::DefenderResigns)?;
+        assert!(matches!(
+            engine_match::outcome(&game, Role::Attacker),
+            Outcome::Win
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn puzzles() -> anyhow::Result<()> {
+        let position = "6XXOK1/4X5X/11/5X4X/X3O6/XX1OO5X/X3OO3X1/X4O2X2/3X2O3X/8XO1/4XXXX3 d 17";
+        let game = Game::from(position.parse::<Position>()?);
+        let finder = PuzzleFinder {
+            solver_nodes: 2_000,
+            ..PuzzleFinder::default()
+        };
+
+        let Some(puzzle) = finder.find(&game) else {
+            panic!("the king escapes");
+        };
+        assert_eq!(puzzle.theme, Theme::KingEscape);
+        assert_eq!(
+            puzzle.to_string(),
+            format!("{position} king_escape J11-K11")
+        );
+        assert_eq!(puzzle.to_string().parse::<Puzzle>()?, puzzle);
+
+        let mut ai = AiBasic {
+            depth: 1,
+            transposition_table: TranspositionTable::new(1),
+            ..AiBasic::default()
+        };
+        let values = ai.play_values(&game);
+        assert_eq!(values.len(), game.all_legal_plays().len());
+        assert_eq!(values[0], (i32::MAX, puzzle.solution[0].clone()));
+
+        // The king can escape to either corner.
+        let board = [
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            "...X.......",
+            "...........",
+            "...........",
+            "...........",
+            "...........",
+            ".......X...",
+            ".K.........",
+        ];
+        let game = Game {
+            board: board.try_into()?,
+            turn: Role::Defender,
+            ..Game::default()
+        };
+        assert!(finder.find(&game).is_none());
+
+        assert!(format!("{position} king_escape").parse::<Puzzle>().is_err());
+        assert!(
+            format!("{position} escape J11-K11")
+                .parse::<Puzzle>()
+                .is_err()
+        );
+        assert!(
+            format!("{position} king_escape J11-H11")
+                .parse::<Puzzle>()
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    // Board Sizes
+
+    #[test]
+    fn vertexes_past_k() -> anyhow::Result<()> {
+        let vertex = Vertex::parse("s19", BoardSize::_19)?;
+        assert_eq!((vertex.x, vertex.y), (18, 0));
+        assert_eq!(vertex.to_string(), "S19");
+
+        let vertex = Vertex::parse("M1", BoardSize::_13)?;
+        assert_eq!((vertex.x, vertex.y), (12, 12));
+        assert_eq!(vertex.to_string(), "M1");
+
+        assert!(Vertex::parse("L1", BoardSize::_11).is_err());
+        assert!(Vertex::parse("A12", BoardSize::_11).is_err());
+        assert!(Vertex::parse("H1", BoardSize::_7).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn captured_more_pieces_than_the_start() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        game.read_line(
+            "set_position 11/XXXXXXXXXXX/XXXXXXXXXXX/XXXXXXXXXXX/11/5K5/11/11/11/11/11 a",
+        )?;
+
+        let captured = game.board.captured();
+        assert_eq!(captured.attacker(), "♟ 0");
+        assert_eq!(captured.defender(), "♙ 12");
+
+        Ok(())
+    }
+
+    #[test]
+    fn board_sizes_without_starting_positions() -> anyhow::Result<()> {
+        for (size, corner) in [(15, "O15"), (17, "Q17")] {
+            let size = BoardSize::try_from(size)?;
+            let vertex = Vertex::parse(corner, size)?;
+            assert_eq!((vertex.x, vertex.y), (size.get() - 1, 0));
+            assert_eq!(
+                usize::from(size.masks().board.count()),
+                size.get() * size.get()
+            );
+            assert!(StartingPosition::try_from(size).is_err());
+        }
+
+        assert!(BoardSize::try_from(21).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn starting_positions() -> anyhow::Result<()> {
+        for (name, size, attackers, defenders) in [
+            ("brandubh", BoardSize::_7, 8, 4),
+            ("tablut", BoardSize::_9, 16, 8),
+            ("copenhagen", BoardSize::_11, 24, 12),
+            ("tawlbwrdd", BoardSize::_11, 24, 12),
+            ("hnefatafl_13", BoardSize::_13, 24, 12),
+            ("alea_evangelii", BoardSize::_19, 48, 24),
+        ] {
+            let mut game = Game::default();
+            game.read_line(&format!("reset_board {name}"))?;
+
+            assert_eq!(game.board.size, size);
+            assert_eq!(game.board.attackers.count(), attackers);
+            assert_eq!(game.board.defenders.count(), defenders);
+            assert_eq!(game.board.king.count(), 1);
+            assert!(game.board.to_string().contains(&format!("{}│", size.get())));
+
+            let string = ron::ser::to_string(&game.board)?;
+            let board: Board = ron::from_str(&string)?;
+            assert_eq!(game.board, board);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn brandubh() -> anyhow::Result<()> {
+        let mut game = Game::default();
+        game.read_line("reset_board brandubh")?;
+
+        let result = game.read_line("play attacker d7 h7");
+        assert!(result.is_err());
+        assert_error_str(result, "play: the first letter is not a legal char");
+
+        game.read_line("play attacker b4 b5")?;
+        game.read_line("play defender d5 c5")?;
+        let captures = game.read_line("play attacker d6 d5")?;
+        assert_eq!(captures, Some("C5 ".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn board_sizes_someone_wins() -> anyhow::Result<()> {
+        for name in ["brandubh", "tablut", "hnefatafl_13", "alea_evangelii"] {
+            let mut game = Game::default();
+            game.read_line(&format!("reset_board {name}"))?;
+            let mut ai: Box<dyn AI> = Box::new(AiBanal);
+
+            while let Some(play) = game.generate_move(&mut ai) {
+                game.play(&play)?;
+            }
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn someone_wins() -> anyhow::Result<()> {
         let mut game = Game::default();
@@ -3,9 +3,14 @@ use std::str::FromStr;
 use anyhow::Context;
 
 use crate::{
+    ai,
+    board::StartingPosition,
+    board_size::BoardSize,
     play::{Plae, Vertex},
+    position::Position,
     role::Role,
-    time,
+    rules::RuleSet,
+    solver, time,
 };
 
 /// hnefatafl-text-protocol binary and javascript pkg
@@ -15,10 +20,16 @@ use crate::{
 /// whitespace or the empty string are passed the engine ignores the input and requests another
 /// string. Comments are any text following `#` on a line.
 ///
+/// When a play is refused the error message starts with a code that won't change: `game_over`,
+/// `wrong_board_size`, `no_piece`, `not_your_turn`, `wrong_piece`, `not_straight_line`, `no_move`,
+/// `blocked_path`, `restricted_square`, `repeated_position`, or `resign_other_player`, for
+/// example `? not_your_turn play: it isn't your turn`.
+///
 /// Valid **ROLE** strings are `a`, `attacker`, `d`, and `defender`. Case does not matter.
 ///
-/// Valid **TO** and **FROM** coordinates are a letter, uppercase or lowercase, `A` though `K`
-/// followed by a number `1` through `11`. For example, `A1`.
+/// Valid **TO** and **FROM** coordinates are a letter, uppercase or lowercase, followed by a
+/// number. On an 11×11 board the letters are `A` though `K` and the numbers are `1` through `11`,
+/// for example `A1`. A 19×19 board goes up to `S19`.
 ///
 /// **MILLISECONDS** and **ADD_SECONDS** are numbers.
 ///
@@ -70,6 +81,23 @@ use crate::{
 #[allow(clippy::doc_markdown)]
 #[derive(Debug, Clone)]
 pub enum Message {
+    /// `ai` | `ai NAME [KEY=VALUE ...]`
+    ///
+    /// Chooses the AI `generate_move` uses, `banal` by default, and returns it when given no
+    /// **NAME**. **NAME** is `banal`, `basic`, or `mcts`, and the options are `depth`, `time`
+    /// (the most seconds to spend on a move), `skill`, `threads`, and `seed` for `basic`, and
+    /// `iterations`, `time`, and `seed` for `mcts`. For example `ai basic depth=4 seed=1`.
+    Ai(Option<ai::AiSettings>),
+
+    /// `analyze` | `analyze DEPTH`
+    ///
+    /// Searches the position with the basic AI and the `ai` settings, down to **DEPTH** plays or
+    /// the depth the settings give, without playing. Returns a line for each depth the search finished, with the format
+    /// `info depth DEPTH nodes NODES nps NODES_PER_SECOND time MILLISECONDS score SCORE pv LINE`.
+    /// **SCORE** is `win`, `loss`, or how good the position is for the side to play, higher being
+    /// better, and **LINE** has the format `FROM-TO FROM-TO ...`, the best play first.
+    Analyze(Option<u64>),
+
     /// The empty string or only comments and whitespace was passed.
     Empty,
 
@@ -123,16 +151,59 @@ pub enum Message {
     /// quits the engine.
     Quit,
 
-    /// `reset_board`
+    /// `reset_board` | `reset_board STARTING_POSITION`
+    ///
+    /// Sets the board to the starting position. **STARTING_POSITION** is one of `brandubh` (7×7),
+    /// `tablut` (9×9), `copenhagen` (11×11), `tawlbwrdd` (11×11), `hnefatafl_13` (13×13), or
+    /// `alea_evangelii` (19×19), it defaults to `copenhagen`.
+    ResetBoard(StartingPosition),
+
+    /// `rules` | `rules copenhagen|fetlar|house [RULE ...]`
+    ///
+    /// Without arguments returns the rules in use. Otherwise chooses the rules, starting from the
+    /// named rule set and changing it by each **RULE**, which is one of `corner_escape`,
+    /// `edge_escape`, `armed_king`, `unarmed_king`, `king_captured_by_2`, `king_captured_by_4`,
+    /// `shield_walls`, `no_shield_walls`, `exit_forts`, `no_exit_forts`, `encirclement`,
+    /// `no_encirclement`, `repetition_attacker`, `repetition_defender`, `repetition_both`,
+    /// `repetition_neither`, `repetition_draw_N`, `no_repetition_draw`, `move_limit_N`,
+    /// `no_move_limit`, `insufficient_material`, or `no_insufficient_material`. With
+    /// `repetition_draw_N` the game is drawn when the attackers reach a position for the **N**th
+    /// time and with `move_limit_N` after **N** plays without a capture. `house` is any other
+    /// rule set, it names none of its own, so one of each of the ten rules has to be given, as
+    /// `rules` returns them. The rules are kept by `reset_board`.
+    Rules(Option<RuleSet>),
+
+    /// `set_position PLACEMENT ROLE [MOVE_NUMBER]`
     ///
-    /// Sets the board to the starting position.
-    ResetBoard,
+    /// Starts a game from a position, see `Position` for the notation. The board size comes from
+    /// the number of rows and the rules are kept.
+    SetPosition(Position),
 
     /// `show_board`
     ///
     /// Displays the board
     ShowBoard,
 
+    /// `show_position`
+    ///
+    /// Returns the position in the notation `set_position` takes.
+    ShowPosition,
+
+    /// `skill LEVEL`
+    ///
+    /// Makes `generate_move` use the basic AI at skill **LEVEL**, from 1 to 20. Skill 20 plays as
+    /// strong as it can, lower skills search less deep and choose worse plays more often. It is
+    /// the same as `ai basic skill=LEVEL`, but keeps the other options of the basic AI.
+    Skill(u8),
+
+    /// `solve` | `solve NODES`
+    ///
+    /// Looks for a forced win or loss for the side to play with proof number search, looking at
+    /// up to **NODES** positions, 100000 by default. Returns `win LINE`, `loss LINE`, or `unknown`,
+    /// where **LINE** has the format `FROM-TO FROM-TO ...`, the plays of the forced line starting
+    /// with the side to play.
+    Solve(u64),
+
     /// `time_settings un-timed` | `time_settings fischer MILLISECONDS ADD_SECONDS`
     ///
     /// Choose the time settings. For fischer time **MILLISECONDS** is the starting time and
@@ -140,13 +211,20 @@ pub enum Message {
     /// which case the time settings are really absolute time.
     TimeSettings(time::TimeSettings),
 
+    /// `undo`
+    ///
+    /// Takes back the last play.
+    Undo,
+
     /// `version`
     ///
     /// Displays the package version.
     Version,
 }
 
-pub static COMMANDS: [&str; 14] = [
+pub static COMMANDS: [&str; 22] = [
+    "ai",
+    "analyze",
     "final_status",
     "generate_move",
     "known_command",
@@ -158,15 +236,34 @@ pub static COMMANDS: [&str; 14] = [
     "protocol_version",
     "quit",
     "reset_board",
+    "rules",
+    "set_position",
     "show_board",
+    "show_position",
+    "skill",
+    "solve",
     "time_settings",
+    "undo",
     "version",
 ];
 
+/// Parses a message for an 11×11 board.
 impl FromStr for Message {
     type Err = anyhow::Error;
 
     fn from_str(message: &str) -> anyhow::Result<Self> {
+        Self::parse(message, BoardSize::default())
+    }
+}
+
+impl Message {
+    /// Parses a message, reading the vertexes as squares on a board of `size`.
+    ///
+    /// # Errors
+    ///
+    /// If the message isn't a valid command.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn parse(message: &str, size: BoardSize) -> anyhow::Result<Self> {
         let args: Vec<&str> = message.split_whitespace().collect();
 
         if args.is_empty() {
@@ -174,6 +271,19 @@ impl FromStr for Message {
         }
 
         match *args.first().unwrap() {
+            "ai" => {
+                if args.len() == 1 {
+                    Ok(Self::Ai(None))
+                } else {
+                    Ok(Self::Ai(Some(ai::AiSettings::from_str(
+                        &args[1..].join(" "),
+                    )?)))
+                }
+            }
+            "analyze" => match args.get(1) {
+                Some(depth) => Ok(Self::Analyze(Some(depth.parse()?))),
+                None => Ok(Self::Analyze(None)),
+            },
             "final_status" => Ok(Self::FinalStatus),
             "generate_move" => Ok(Self::GenerateMove),
             "known_command" => Ok(Self::KnownCommand(
@@ -182,14 +292,14 @@ impl FromStr for Message {
             "list_commands" => Ok(Self::ListCommands),
             "name" => Ok(Self::Name),
             "play" => {
-                let play = Plae::try_from(args)?;
+                let play = Plae::parse(&args, size)?;
                 Ok(Self::Play(play))
             }
             "play_from" => Ok(Self::PlayFrom),
             "play_to" => {
                 if let (Some(role), Some(vertex)) = (args.get(1), args.get(2)) {
                     let role = Role::from_str(role)?;
-                    let vertex = Vertex::from_str(vertex)?;
+                    let vertex = Vertex::parse(vertex, size)?;
                     Ok(Self::PlayTo((role, vertex)))
                 } else {
                     Err(anyhow::Error::msg("expected: play_to role vertex"))
@@ -197,12 +307,48 @@ impl FromStr for Message {
             }
             "protocol_version" => Ok(Self::ProtocolVersion),
             "quit" => Ok(Self::Quit),
-            "reset_board" => Ok(Self::ResetBoard),
+            "reset_board" => {
+                if let Some(starting_position) = args.get(1) {
+                    Ok(Self::ResetBoard(StartingPosition::from_str(
+                        starting_position,
+                    )?))
+                } else {
+                    Ok(Self::ResetBoard(StartingPosition::default()))
+                }
+            }
+            "rules" => {
+                if args.len() == 1 {
+                    Ok(Self::Rules(None))
+                } else {
+                    Ok(Self::Rules(Some(RuleSet::try_from(args)?)))
+                }
+            }
+            "set_position" => Ok(Self::SetPosition(Position::from_str(&args[1..].join(" "))?)),
             "show_board" => Ok(Self::ShowBoard),
+            "show_position" => Ok(Self::ShowPosition),
+            "skill" => {
+                let Some(skill) = args.get(1) else {
+                    return Err(anyhow::Error::msg("expected: skill LEVEL"));
+                };
+                let skill = skill.parse()?;
+                if !(1..=ai::MAX_SKILL).contains(&skill) {
+                    return Err(anyhow::Error::msg(format!(
+                        "the skill has to be from 1 to {}",
+                        ai::MAX_SKILL
+                    )));
+                }
+
+                Ok(Self::Skill(skill))
+            }
+            "solve" => match args.get(1) {
+                Some(nodes) => Ok(Self::Solve(nodes.parse()?)),
+                None => Ok(Self::Solve(solver::DEFAULT_NODES)),
+            },
             "time_settings" => {
                 let time_settings = time::TimeSettings::try_from(args)?;
                 Ok(Self::TimeSettings(time_settings))
             }
+            "undo" => Ok(Self::Undo),
             "version" => Ok(Self::Version),
             text => {
                 if text.trim().is_empty() {
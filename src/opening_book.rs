@@ -0,0 +1,270 @@
+use std::{fs, path::Path};
+
+use rand::{Rng, distributions::WeightedIndex, prelude::Distribution};
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::Game,
+    play::{Plae, Play, Vertex},
+    role::Role,
+    space::Space,
+    status::Status,
+    zobrist::ZOBRIST,
+};
+
+/// How many plays into a game the book goes if it isn't told.
+pub const DEFAULT_PLAYS: usize = 12;
+
+/// The plays made from the positions at the start of finished games, and how those games went.
+///
+/// A position and its mirror images and rotations are the same position in the book. It is keyed
+/// by the smallest Zobrist hash of the eight, and its plays are turned the same way.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OpeningBook {
+    pub positions: FxHashMap<u64, Vec<BookPlay>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BookPlay {
+    pub play: Play,
+    /// The games the play was made in.
+    pub games: u32,
+    /// The games the side that made the play won.
+    pub wins: u32,
+    pub draws: u32,
+}
+
+impl BookPlay {
+    /// The share of the games the side that made the play won, a draw counts as half a win.
+    #[must_use]
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+
+        (f64::from(self.wins) + f64::from(self.draws) / 2.0) / f64::from(self.games)
+    }
+}
+
+impl OpeningBook {
+    /// Loads a book from a RON file if the path ends in `.ron`, otherwise from a postcard file.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read or isn't an `OpeningBook`.
+    pub fn from_path(path: &Path) -> anyhow::Result<Self> {
+        if is_ron(path) {
+            Ok(ron::from_str(&fs::read_to_string(path)?)?)
+        } else {
+            Ok(postcard::from_bytes(&fs::read(path)?)?)
+        }
+    }
+
+    /// Saves the book as RON if the path ends in `.ron`, otherwise as postcard.
+    ///
+    /// # Errors
+    ///
+    /// If the book can't be serialized or the file can't be written.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if is_ron(path) {
+            let pretty = ron::ser::PrettyConfig::default();
+            fs::write(path, ron::ser::to_string_pretty(self, pretty)?)?;
+        } else {
+            fs::write(path, postcard::to_allocvec(self)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the first `depth` `plays` of a game of Copenhagen that ended with `status`. A game
+    /// that didn't end adds nothing, and one with an illegal play adds the plays before it.
+    pub fn add_game(
+        &mut self,
+        plays: impl IntoIterator<Item = Plae>,
+        status: &Status,
+        depth: usize,
+    ) {
+        let winner = match status {
+            Status::AttackerWins => Role::Attacker,
+            Status::DefenderWins => Role::Defender,
+            Status::Draw => Role::Roleless,
+            Status::Ongoing => return,
+        };
+
+        let mut game = Game::default();
+
+        for play in plays.into_iter().take(depth) {
+            let Plae::Play(play) = play else {
+                break;
+            };
+
+            let (key, symmetries) = canonical(&game);
+            if game.play(&Plae::Play(play.clone())).is_err() {
+                break;
+            }
+
+            // A position that is its own mirror image has more than one way to turn the play.
+            let Some(play) = symmetries
+                .into_iter()
+                .map(|symmetry| Play {
+                    role: play.role,
+                    from: transform(&play.from, symmetry),
+                    to: transform(&play.to, symmetry),
+                })
+                .min()
+            else {
+                break;
+            };
+
+            let plays = self.positions.entry(key).or_default();
+            if !plays.iter().any(|book_play| book_play.play == play) {
+                plays.push(BookPlay {
+                    play: play.clone(),
+                    games: 0,
+                    wins: 0,
+                    draws: 0,
+                });
+            }
+
+            let Some(book_play) = plays.iter_mut().find(|book_play| book_play.play == play) else {
+                continue;
+            };
+            book_play.games += 1;
+            if winner == play.role {
+                book_play.wins += 1;
+            } else if winner == Role::Roleless {
+                book_play.draws += 1;
+            }
+        }
+    }
+
+    /// The plays in the book from the position of `game`, turned to fit the board as it stands.
+    #[must_use]
+    pub fn plays(&self, game: &Game) -> Vec<BookPlay> {
+        let (key, symmetries) = canonical(game);
+        let Some(plays) = self.positions.get(&key) else {
+            return Vec::new();
+        };
+
+        let inverse = inverse(symmetries[0]);
+        plays
+            .iter()
+            .map(|book_play| BookPlay {
+                play: Play {
+                    role: book_play.play.role,
+                    from: transform(&book_play.play.from, inverse),
+                    to: transform(&book_play.play.to, inverse),
+                },
+                ..book_play.clone()
+            })
+            .collect()
+    }
+
+    /// The legal play from the book made in the most games, or `None` if the position isn't in
+    /// the book.
+    #[must_use]
+    pub fn most_played(&self, game: &Game) -> Option<Plae> {
+        if game.status != Status::Ongoing {
+            return None;
+        }
+
+        self.plays(game)
+            .into_iter()
+            .filter(|book_play| {
+                book_play.play.role == game.turn
+                    && game.legal_moves().any(|play| play == book_play.play)
+            })
+            .max_by_key(|book_play| book_play.games)
+            .map(|book_play| Plae::Play(book_play.play))
+    }
+
+    /// Picks a legal play from the book at random, weighted by how often it was made times how
+    /// often it won. Returns `None` if the position isn't in the book or none of its plays won.
+    pub fn choose(&self, game: &Game, rng: &mut impl Rng) -> Option<Plae> {
+        if game.status != Status::Ongoing {
+            return None;
+        }
+
+        let plays: Vec<_> = self
+            .plays(game)
+            .into_iter()
+            .filter(|book_play| {
+                book_play.play.role == game.turn
+                    && game.legal_moves().any(|play| play == book_play.play)
+            })
+            .collect();
+
+        let weights = plays
+            .iter()
+            .map(|book_play| f64::from(book_play.games) * book_play.win_rate());
+        let index = WeightedIndex::new(weights).ok()?.sample(rng);
+
+        Some(Plae::Play(plays[index].play.clone()))
+    }
+}
+
+fn is_ron(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "ron")
+}
+
+/// The smallest key of the position as the symmetries see it, and the symmetries that give it.
+fn canonical(game: &Game) -> (u64, Vec<u8>) {
+    let board = &game.board;
+    let size = board.size;
+    let pieces = [
+        (board.attackers, Space::Attacker),
+        (board.defenders, Space::Defender),
+        (board.king, Space::King),
+    ];
+
+    let keys: Vec<_> = (0..8)
+        .map(|symmetry| {
+            let mut key = ZOBRIST.side_to_play(game.turn);
+            for (bitboard, space) in pieces {
+                for vertex in bitboard.vertexes(size) {
+                    key ^= ZOBRIST.piece(&transform(&vertex, symmetry), space);
+                }
+            }
+
+            key
+        })
+        .collect();
+
+    let key = keys.iter().copied().min().unwrap_or_default();
+    let symmetries = (0..8).filter(|symmetry| keys[usize::from(*symmetry)] == key);
+
+    (key, symmetries.collect())
+}
+
+/// Mirrors and rotates the vertex by one of the eight symmetries of the square board.
+fn transform(vertex: &Vertex, symmetry: u8) -> Vertex {
+    let last = vertex.size.get() - 1;
+    let (x, y) = (vertex.x, vertex.y);
+
+    let (x, y) = match symmetry {
+        0 => (x, y),
+        1 => (last - x, y),
+        2 => (x, last - y),
+        3 => (last - x, last - y),
+        4 => (y, x),
+        5 => (last - y, x),
+        6 => (y, last - x),
+        _ => (last - y, last - x),
+    };
+
+    Vertex {
+        size: vertex.size,
+        x,
+        y,
+    }
+}
+
+/// The symmetry that undoes `symmetry`, the quarter turns undo each other.
+fn inverse(symmetry: u8) -> u8 {
+    match symmetry {
+        5 => 6,
+        6 => 5,
+        symmetry => symmetry,
+    }
+}
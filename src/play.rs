@@ -3,9 +3,10 @@ use std::{fmt, str::FromStr};
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
-use crate::{role::Role, time::TimeSettings};
+use crate::{board_size::BoardSize, role::Role, time::TimeSettings};
 
-pub const BOARD_LETTERS: &str = "ABCDEFGHIJK";
+/// The letters of the files, a board uses as many of them as it is wide.
+pub const BOARD_LETTERS: &str = "ABCDEFGHIJKLMNOPQRS";
 
 #[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct PlayRecord {
@@ -36,23 +37,24 @@ impl Plae {
     /// # Errors
     ///
     /// If you try to convert an illegal character or you don't get vertex-vertex.
-    pub fn from_str_(play: &str, role: &Role) -> anyhow::Result<Self> {
+    pub fn from_str_(play: &str, role: &Role, size: BoardSize) -> anyhow::Result<Self> {
         let Some((from, to)) = play.split_once('-') else {
             return Err(anyhow::Error::msg("expected: vertex-vertex"));
         };
 
         Ok(Self::Play(Play {
             role: *role,
-            from: Vertex::from_str(from)?,
-            to: Vertex::from_str(to)?,
+            from: Vertex::parse(from, size)?,
+            to: Vertex::parse(to, size)?,
         }))
     }
-}
-
-impl TryFrom<Vec<&str>> for Plae {
-    type Error = anyhow::Error;
 
-    fn try_from(args: Vec<&str>) -> Result<Self, Self::Error> {
+    /// Parses `play ROLE FROM TO` or `play ROLE resign` on a board of `size`.
+    ///
+    /// # Errors
+    ///
+    /// If the arguments aren't a valid play.
+    pub fn parse(args: &[&str], size: BoardSize) -> anyhow::Result<Self> {
         let error_str = "expected: 'play ROLE FROM TO' or 'play ROLE resign'";
 
         if args.len() < 3 {
@@ -74,12 +76,20 @@ impl TryFrom<Vec<&str>> for Plae {
 
         Ok(Self::Play(Play {
             role: Role::from_str(args[1])?,
-            from: Vertex::from_str(args[2])?,
-            to: Vertex::from_str(args[3])?,
+            from: Vertex::parse(args[2], size)?,
+            to: Vertex::parse(args[3], size)?,
         }))
     }
 }
 
+impl TryFrom<Vec<&str>> for Plae {
+    type Error = anyhow::Error;
+
+    fn try_from(args: Vec<&str>) -> Result<Self, Self::Error> {
+        Self::parse(&args, BoardSize::default())
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct Plays(pub Vec<PlayRecord>);
 
@@ -125,60 +135,161 @@ impl fmt::Display for Captures {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+/// A square on the board, `y` counts down from the top row.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Vertex {
+    pub size: BoardSize,
     pub x: usize,
     pub y: usize,
 }
 
+/// How a `Vertex` is serialized in formats that name the fields, the size is left out on an
+/// 11×11 board, the way every vertex was written before boards came in other sizes.
+#[derive(Deserialize, Serialize)]
+#[serde(rename = "Vertex")]
+struct NamedVertex {
+    #[serde(default, skip_serializing_if = "is_11")]
+    size: BoardSize,
+    x: usize,
+    y: usize,
+}
+
+/// How a `Vertex` is serialized in binary formats, which can't leave out a field. On a board that
+/// isn't 11×11 the size is in the bits of `x` above `FILE_BITS`, an 11×11 vertex has none.
+#[derive(Deserialize, Serialize)]
+#[serde(rename = "Vertex")]
+struct PackedVertex {
+    x: usize,
+    y: usize,
+}
+
+/// Enough bits for the files of a 19×19 board.
+const FILE_BITS: usize = 5;
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_11(size: &BoardSize) -> bool {
+    *size == BoardSize::_11
+}
+
+impl Serialize for Vertex {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            return NamedVertex {
+                size: self.size,
+                x: self.x,
+                y: self.y,
+            }
+            .serialize(serializer);
+        }
+
+        let size = if is_11(&self.size) {
+            0
+        } else {
+            self.size.get() << FILE_BITS
+        };
+
+        PackedVertex {
+            x: size | self.x,
+            y: self.y,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Vertex {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let vertex = if deserializer.is_human_readable() {
+            let vertex = NamedVertex::deserialize(deserializer)?;
+            Self {
+                size: vertex.size,
+                x: vertex.x,
+                y: vertex.y,
+            }
+        } else {
+            let vertex = PackedVertex::deserialize(deserializer)?;
+            let size = match vertex.x >> FILE_BITS {
+                0 => BoardSize::_11,
+                size => {
+                    BoardSize::try_from(size).map_err(<D::Error as serde::de::Error>::custom)?
+                }
+            };
+
+            Self {
+                size,
+                x: vertex.x & ((1 << FILE_BITS) - 1),
+                y: vertex.y,
+            }
+        };
+
+        if vertex.x >= vertex.size.get() || vertex.y >= vertex.size.get() {
+            return Err(<D::Error as serde::de::Error>::custom(format!(
+                "the vertex isn't on a {0}×{0} board",
+                vertex.size
+            )));
+        }
+
+        Ok(vertex)
+    }
+}
+
 impl fmt::Display for Vertex {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "{}{}",
             BOARD_LETTERS.chars().collect::<Vec<_>>()[self.x],
-            11 - self.y
+            self.size.get() - self.y
         )
     }
 }
 
+/// Parses a vertex on an 11×11 board.
 impl FromStr for Vertex {
     type Err = anyhow::Error;
 
     fn from_str(vertex: &str) -> anyhow::Result<Self> {
+        Self::parse(vertex, BoardSize::default())
+    }
+}
+
+impl Vertex {
+    /// Parses a vertex on a board of `size`, for example `A1` or `M13`.
+    ///
+    /// # Errors
+    ///
+    /// If the vertex isn't on the board.
+    pub fn parse(vertex: &str, size: BoardSize) -> anyhow::Result<Self> {
         let mut chars = vertex.chars();
 
         if let Some(mut ch) = chars.next() {
             ch = ch.to_ascii_uppercase();
-            let x = BOARD_LETTERS
+            let x = BOARD_LETTERS[..size.get()]
                 .find(ch)
                 .context("play: the first letter is not a legal char")?;
 
-            let mut y = chars.as_str().parse()?;
-            if y > 0 && y < 12 {
-                y = 11 - y;
-                return Ok(Self { x, y });
+            let y: usize = chars.as_str().parse()?;
+            if y > 0 && y <= size.get() {
+                return Ok(Self {
+                    size,
+                    x,
+                    y: size.get() - y,
+                });
             }
         }
 
         Err(anyhow::Error::msg("play: invalid coordinate"))
     }
-}
 
-impl Vertex {
     #[must_use]
     pub fn fmt_other(&self) -> String {
-        format!(
-            "{}{}",
-            BOARD_LETTERS.chars().collect::<Vec<_>>()[self.x],
-            11 - self.y
-        )
+        self.to_string()
     }
 
     #[must_use]
     pub fn up(&self) -> Option<Vertex> {
         if self.y > 0 {
             Some(Vertex {
+                size: self.size,
                 x: self.x,
                 y: self.y - 1,
             })
@@ -191,6 +302,7 @@ impl Vertex {
     pub fn left(&self) -> Option<Vertex> {
         if self.x > 0 {
             Some(Vertex {
+                size: self.size,
                 x: self.x - 1,
                 y: self.y,
             })
@@ -201,8 +313,9 @@ impl Vertex {
 
     #[must_use]
     pub fn down(&self) -> Option<Vertex> {
-        if self.y < 10 {
+        if self.y < self.size.get() - 1 {
             Some(Vertex {
+                size: self.size,
                 x: self.x,
                 y: self.y + 1,
             })
@@ -213,8 +326,9 @@ impl Vertex {
 
     #[must_use]
     pub fn right(&self) -> Option<Vertex> {
-        if self.x < 10 {
+        if self.x < self.size.get() - 1 {
             Some(Vertex {
+                size: self.size,
                 x: self.x + 1,
                 y: self.y,
             })
@@ -225,6 +339,7 @@ impl Vertex {
 
     #[must_use]
     pub fn touches_wall(&self) -> bool {
-        self.x == 0 || self.x == 10 || self.y == 0 || self.y == 10
+        let last = self.size.get() - 1;
+        self.x == 0 || self.x == last || self.y == 0 || self.y == last
     }
 }
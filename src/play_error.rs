@@ -0,0 +1,85 @@
+use std::fmt;
+
+use crate::{board_size::BoardSize, role::Role};
+
+/// Why a play was refused.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PlayError {
+    /// The game is already over.
+    GameOver,
+    /// A vertex is for a board of another size.
+    WrongBoardSize(BoardSize),
+    /// There is no piece on the square played from.
+    NoPiece,
+    /// The piece belongs to the side that isn't playing.
+    NotYourTurn,
+    /// The play names a different role than the piece it moves.
+    WrongPiece { piece: Role, play: Role },
+    /// The play isn't along a row or a column.
+    NotStraightLine,
+    /// The play ends where it started.
+    NoMove,
+    /// A piece stands between the squares played from and to.
+    BlockedPath,
+    /// Only the king may stop on the square played to.
+    RestrictedSquare,
+    /// The side playing may not reach a position twice.
+    RepeatedPosition,
+    /// A player tried to resign for the other side.
+    ResignOtherPlayer,
+}
+
+impl PlayError {
+    /// A stable name for the error, the text protocol sends it before the message.
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::GameOver => "game_over",
+            Self::WrongBoardSize(_) => "wrong_board_size",
+            Self::NoPiece => "no_piece",
+            Self::NotYourTurn => "not_your_turn",
+            Self::WrongPiece { .. } => "wrong_piece",
+            Self::NotStraightLine => "not_straight_line",
+            Self::NoMove => "no_move",
+            Self::BlockedPath => "blocked_path",
+            Self::RestrictedSquare => "restricted_square",
+            Self::RepeatedPosition => "repeated_position",
+            Self::ResignOtherPlayer => "resign_other_player",
+        }
+    }
+}
+
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GameOver => write!(f, "play: the game is already over"),
+            Self::WrongBoardSize(size) => write!(f, "play: the board is {size}x{size}"),
+            Self::NoPiece => write!(f, "play: you didn't select a role"),
+            Self::NotYourTurn => write!(f, "play: it isn't your turn"),
+            Self::WrongPiece { piece, play } => write!(
+                f,
+                "play: you are trying to move {piece}, but it's {play}'s turn"
+            ),
+            Self::NotStraightLine => write!(f, "play: you can only play in a straight line"),
+            Self::NoMove => write!(f, "play: you have to change location"),
+            Self::BlockedPath => write!(f, "play: you have to play through empty locations"),
+            Self::RestrictedSquare => {
+                write!(f, "play: only the king may move to a restricted square")
+            }
+            Self::RepeatedPosition => write!(f, "play: you already reached that position"),
+            Self::ResignOtherPlayer => write!(f, "You can't resign for the other player."),
+        }
+    }
+}
+
+impl std::error::Error for PlayError {}
+
+/// What the text protocol says after `?` when a command fails. A `PlayError` starts with its code.
+#[must_use]
+pub fn error_message(error: &anyhow::Error) -> String {
+    if let Some(play_error) = error.downcast_ref::<PlayError>() {
+        format!("{} {play_error}", play_error.code())
+    } else {
+        error.to_string()
+    }
+}
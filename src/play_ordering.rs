@@ -0,0 +1,126 @@
+use std::cmp::Reverse;
+
+use crate::{
+    game::Game,
+    play::{Plae, Play, Vertex},
+};
+
+const TABLE_PLAY: u64 = u64::MAX;
+const ESCAPE: u64 = 1 << 62;
+const CAPTURE: u64 = 1 << 61;
+const KING_TOWARD_CORNER: u64 = 1 << 60;
+const KILLER_1: u64 = 1 << 59;
+const KILLER_2: u64 = KILLER_1 - 1;
+
+/// What the search learned about which plays to try first.
+///
+/// After the play from the transposition table come escapes, captures, and king moves toward a
+/// corner, then the killer plays, which caused a cutoff as many plays from the root, and last the
+/// quiet plays by how often they caused a cutoff anywhere.
+#[derive(Clone, Debug, Default)]
+pub struct PlayOrdering {
+    killers: Vec<[Option<Play>; 2]>,
+    history: Vec<u64>,
+}
+
+impl PlayOrdering {
+    /// Forgets the killers and half of the history, the position has moved on.
+    pub fn new_search(&mut self, game: &Game) {
+        self.killers.clear();
+
+        let squares = game.board.size.get() * game.board.size.get();
+        if self.history.len() == squares * squares {
+            for count in &mut self.history {
+                *count /= 2;
+            }
+        } else {
+            self.history = vec![0; squares * squares];
+        }
+    }
+
+    /// Sorts `plays` so the best ones come first, `table_play` is the best play stored for the
+    /// position and `from_root` how many plays it is from the position searched.
+    pub fn order(
+        &self,
+        game: &Game,
+        plays: &mut [Plae],
+        table_play: Option<&Plae>,
+        from_root: usize,
+    ) {
+        plays.sort_by_cached_key(|play| {
+            if Some(play) == table_play {
+                return Reverse(TABLE_PLAY);
+            }
+
+            match play {
+                Plae::Play(play) => Reverse(self.score(game, play, from_root)),
+                Plae::AttackerResigns | Plae::DefenderResigns => Reverse(0),
+            }
+        });
+    }
+
+    /// Remembers that `play` was good enough to skip the rest of the plays.
+    pub fn cutoff(&mut self, game: &Game, play: &Plae, from_root: usize, depth: u64) {
+        let Plae::Play(play) = play else {
+            return;
+        };
+        if game.board.captures_a_piece(play, &game.rules) {
+            return;
+        }
+
+        if self.killers.len() <= from_root {
+            self.killers.resize(from_root + 1, [None, None]);
+        }
+        let killers = &mut self.killers[from_root];
+        if killers[0].as_ref() != Some(play) {
+            killers[1] = killers[0].take();
+            killers[0] = Some(play.clone());
+        }
+
+        if let Some(count) = self.history.get_mut(history_index(play)) {
+            *count += depth * depth;
+        }
+    }
+
+    fn score(&self, game: &Game, play: &Play, from_root: usize) -> u64 {
+        let king = game.board.king.contains(&play.from);
+
+        if king && game.rules.exits(game.board.size).contains(&play.to) {
+            return ESCAPE;
+        }
+        if game.board.captures_a_piece(play, &game.rules) {
+            return CAPTURE;
+        }
+        if king && corner_distance(&play.to) < corner_distance(&play.from) {
+            return KING_TOWARD_CORNER;
+        }
+
+        if let Some(killers) = self.killers.get(from_root) {
+            if killers[0].as_ref() == Some(play) {
+                return KILLER_1;
+            }
+            if killers[1].as_ref() == Some(play) {
+                return KILLER_2;
+            }
+        }
+
+        self.history
+            .get(history_index(play))
+            .copied()
+            .unwrap_or_default()
+            .min(KILLER_2 - 1)
+    }
+}
+
+/// How many squares away the nearest corner is.
+fn corner_distance(vertex: &Vertex) -> usize {
+    let far = vertex.size.get() - 1;
+    vertex.x.min(far - vertex.x) + vertex.y.min(far - vertex.y)
+}
+
+fn history_index(play: &Play) -> usize {
+    let size = play.from.size.get();
+    let index = |vertex: &Vertex| vertex.y * size + vertex.x;
+
+    index(&play.from) * size * size + index(&play.to)
+}
@@ -0,0 +1,195 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    board::Board,
+    game::{Game, PreviousBoards},
+    play::Vertex,
+    role::Role,
+    space::Space,
+};
+
+/// A position written on one line, like FEN in chess.
+///
+/// The rows go from the top of the board to the bottom separated by `/`, `X` is an attacker, `O`
+/// a defender, `K` the king, and a number that many empty squares. Then comes `a` or `d` for the
+/// side to play and the move number, which counts up after the defender plays. For example the
+/// Brandubh starting position is `3X3/3X3/3O3/XXOKOXX/3O3/3X3/3X3 a 1`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Position {
+    pub board: Board,
+    pub turn: Role,
+    pub move_number: usize,
+}
+
+impl Position {
+    /// The position `game` started from, before any of its plays.
+    #[must_use]
+    pub fn start(game: &Game) -> Self {
+        if game.plays.0.is_empty() {
+            return Self::from(game);
+        }
+
+        Self {
+            board: game.start.clone(),
+            turn: if game.plays_before % 2 == 0 {
+                Role::Attacker
+            } else {
+                Role::Defender
+            },
+            move_number: game.plays_before / 2 + 1,
+        }
+    }
+
+    /// How many plays come before this position.
+    #[must_use]
+    pub fn plays(&self) -> usize {
+        (self.move_number - 1) * 2 + usize::from(self.turn == Role::Defender)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = self.board.size;
+        let mut rows = Vec::new();
+
+        for y in 0..size.get() {
+            let mut row = String::new();
+            let mut empty = 0;
+
+            for x in 0..size.get() {
+                let ch = match self.board.get(&Vertex { size, x, y }) {
+                    Space::Empty => {
+                        empty += 1;
+                        continue;
+                    }
+                    Space::Attacker => 'X',
+                    Space::King => 'K',
+                    Space::Defender => 'O',
+                };
+
+                if empty > 0 {
+                    row.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                row.push(ch);
+            }
+
+            if empty > 0 {
+                row.push_str(&empty.to_string());
+            }
+            rows.push(row);
+        }
+
+        let turn = match self.turn {
+            Role::Attacker => "a",
+            Role::Defender => "d",
+            Role::Roleless => "-",
+        };
+
+        write!(f, "{} {turn} {}", rows.join("/"), self.move_number)
+    }
+}
+
+impl FromStr for Position {
+    type Err = anyhow::Error;
+
+    /// Parses `PLACEMENT ROLE [MOVE_NUMBER]`, the move number defaults to 1.
+    fn from_str(position: &str) -> anyhow::Result<Self> {
+        let err_msg = "expected: PLACEMENT ROLE [MOVE_NUMBER]";
+        let args: Vec<&str> = position.split_ascii_whitespace().collect();
+
+        let (placement, turn) = match args.as_slice() {
+            [placement, turn] | [placement, turn, _] => (placement, Role::from_str(turn)?),
+            _ => return Err(anyhow::Error::msg(err_msg)),
+        };
+
+        let move_number = match args.get(2) {
+            Some(move_number) => move_number.parse()?,
+            None => 1,
+        };
+        if move_number == 0 {
+            return Err(anyhow::Error::msg("position: the move number starts at 1"));
+        }
+
+        let size = placement.split('/').count();
+        let rows = placement
+            .split('/')
+            .map(|row| expand_row(row, size))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+
+        Ok(Self {
+            board: Board::try_from(rows.as_slice())?,
+            turn,
+            move_number,
+        })
+    }
+}
+
+impl From<Board> for Position {
+    fn from(board: Board) -> Self {
+        Self {
+            board,
+            turn: Role::Attacker,
+            move_number: 1,
+        }
+    }
+}
+
+impl From<Position> for Board {
+    fn from(position: Position) -> Self {
+        position.board
+    }
+}
+
+impl From<&Game> for Position {
+    fn from(game: &Game) -> Self {
+        let plays = game.plays_before + game.plays.0.len();
+
+        Self {
+            board: game.board.clone(),
+            turn: game.turn,
+            move_number: plays / 2 + 1,
+        }
+    }
+}
+
+impl From<Position> for Game {
+    fn from(position: Position) -> Self {
+        Self {
+            plays_before: position.plays(),
+            previous_boards: PreviousBoards::new(&position.board),
+            start: position.board.clone(),
+            board: position.board,
+            turn: position.turn,
+            ..Self::default()
+        }
+    }
+}
+
+/// Writes each run of empty squares in `row` as dots, a row longer than `size` squares is an
+/// error.
+fn expand_row(row: &str, size: usize) -> anyhow::Result<String> {
+    let too_long = || anyhow::Error::msg(format!("position: a row is longer than {size} squares"));
+    let mut squares = String::new();
+    let mut empty = 0;
+
+    for ch in row.chars() {
+        if let Some(digit) = ch.to_digit(10) {
+            empty = empty * 10 + digit as usize;
+            if squares.len() + empty > size {
+                return Err(too_long());
+            }
+        } else {
+            squares.push_str(&".".repeat(empty));
+            empty = 0;
+            squares.push(ch);
+            if squares.len() > size {
+                return Err(too_long());
+            }
+        }
+    }
+
+    squares.push_str(&".".repeat(empty));
+    Ok(squares)
+}
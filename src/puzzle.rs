@@ -0,0 +1,253 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    ai::AiBasic,
+    game::{Game, TimeUnix},
+    game_end::GameEnd,
+    play::Plae,
+    position::Position,
+    solver,
+    time::TimeSettings,
+    transposition_table::TranspositionTable,
+};
+
+/// What the side to play gets by finding the solution.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Theme {
+    /// The king escapes whatever the attackers play.
+    KingEscape,
+    /// The king is captured whatever the defenders play.
+    KingCapture,
+    /// More pieces are captured than lost, whatever the other side plays.
+    Capture,
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KingEscape => write!(f, "king_escape"),
+            Self::KingCapture => write!(f, "king_capture"),
+            Self::Capture => write!(f, "capture"),
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = anyhow::Error;
+
+    fn from_str(theme: &str) -> anyhow::Result<Self> {
+        match theme {
+            "king_escape" => Ok(Self::KingEscape),
+            "king_capture" => Ok(Self::KingCapture),
+            "capture" => Ok(Self::Capture),
+            _ => Err(anyhow::Error::msg(format!("invalid theme: {theme}"))),
+        }
+    }
+}
+
+/// A position with one best play, written on one line as:
+///
+/// `POSITION THEME SOLUTION`
+///
+/// **SOLUTION** has the format `FROM-TO FROM-TO ...`, the play to find and the line it starts.
+/// For example `6XXOK1/4X5X/11/5X4X/X3O6/XX1OO5X/X3OO3X1/X4O2X2/3X2O3X/8XO1/4XXXX3 d 17
+/// king_escape J11-K11`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Puzzle {
+    pub position: Position,
+    pub theme: Theme,
+    pub solution: Vec<Plae>,
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.position, self.theme)?;
+
+        for play in &self.solution {
+            match play {
+                Plae::Play(play) => write!(f, " {}-{}", play.from, play.to)?,
+                Plae::AttackerResigns | Plae::DefenderResigns => write!(f, " resigns")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = anyhow::Error;
+
+    /// Parses a puzzle and checks that its solution can be played.
+    fn from_str(puzzle: &str) -> anyhow::Result<Self> {
+        let err_msg = "expected: PLACEMENT ROLE MOVE_NUMBER THEME SOLUTION";
+        let args: Vec<&str> = puzzle.split_ascii_whitespace().collect();
+
+        let [placement, role, move_number, theme, solution @ ..] = args.as_slice() else {
+            return Err(anyhow::Error::msg(err_msg));
+        };
+        if solution.is_empty() {
+            return Err(anyhow::Error::msg(err_msg));
+        }
+
+        let position = Position::from_str(&format!("{placement} {role} {move_number}"))?;
+        let mut game = Game::from(position.clone());
+
+        let mut plays = Vec::new();
+        for play in solution {
+            let play = Plae::from_str_(play, &game.turn, game.board.size)?;
+            game.play(&play)?;
+            plays.push(play);
+        }
+
+        Ok(Self {
+            position,
+            theme: Theme::from_str(theme)?,
+            solution: plays,
+        })
+    }
+}
+
+/// Looks for puzzles in positions from real games.
+#[derive(Clone, Debug)]
+pub struct PuzzleFinder {
+    /// How many positions the solver looks at for a forced win, 0 skips them.
+    pub solver_nodes: u64,
+    /// How deep the search looks for captures and checks that the solution is the only one.
+    pub depth: u64,
+    /// How many more pieces the side to play has to capture than it loses for a capture puzzle.
+    pub captures: usize,
+    /// How much better the value of the solution has to be than the next best play for a
+    /// capture puzzle.
+    pub margin: i32,
+}
+
+impl Default for PuzzleFinder {
+    fn default() -> Self {
+        Self {
+            solver_nodes: 20_000,
+            depth: 3,
+            captures: 2,
+            margin: 40,
+        }
+    }
+}
+
+impl PuzzleFinder {
+    /// The puzzle in the position of `game`, if the side to play has a forced win or wins
+    /// material by force, and the search finds only one play that does it.
+    #[must_use]
+    pub fn find(&self, game: &Game) -> Option<Puzzle> {
+        // The clock doesn't matter for a puzzle, and the search would spend it.
+        let mut game = game.clone();
+        game.attacker_time = TimeSettings::UnTimed;
+        game.defender_time = TimeSettings::UnTimed;
+        game.time = TimeUnix::UnTimed;
+
+        if self.solver_nodes > 0 {
+            if let Some(line) = solver::prove(&game, game.turn, self.solver_nodes) {
+                return self.win(&game, line);
+            }
+        }
+
+        self.capture(&game)
+    }
+
+    fn win(&self, game: &Game, line: Vec<Plae>) -> Option<Puzzle> {
+        let mut end = game.clone();
+        for play in &line {
+            end.play(play).ok()?;
+        }
+
+        let theme = match end.game_end? {
+            GameEnd::Escape | GameEnd::ExitFort => Theme::KingEscape,
+            GameEnd::KingCaptured => Theme::KingCapture,
+            _ => return None,
+        };
+
+        // Another play that wins as quickly makes two solutions.
+        let values = search(line.len() as u64).play_values(game);
+        let wins = values.iter().filter(|(value, _play)| *value == i32::MAX);
+        if wins.count() > 1 {
+            return None;
+        }
+
+        // So does one that wins more slowly.
+        for play in game.all_legal_plays() {
+            if line.first() == Some(&play) {
+                continue;
+            }
+
+            let mut game = game.clone();
+            game.play(&play).ok()?;
+            if solver::prove(&game, game.turn.opposite(), self.solver_nodes / 10).is_some() {
+                return None;
+            }
+        }
+
+        Some(Puzzle {
+            position: Position::from(game),
+            theme,
+            solution: line,
+        })
+    }
+
+    fn capture(&self, game: &Game) -> Option<Puzzle> {
+        // Most positions have nothing to capture, and the search is slow.
+        let captures = game.all_legal_plays().into_iter().any(|play| {
+            let mut game = game.clone();
+            game.play(&play)
+                .is_ok_and(|captures| !captures.0.is_empty())
+        });
+        if !captures {
+            return None;
+        }
+
+        let mut ai = search(self.depth);
+        let values = ai.play_values(game);
+        let [(best, play), (next_best, _), ..] = values.as_slice() else {
+            return None;
+        };
+        if best.saturating_sub(*next_best) < self.margin {
+            return None;
+        }
+
+        let pv = ai.analysis.last()?.pv.clone();
+        if pv.first() != Some(play) {
+            return None;
+        }
+
+        // Count the captures of each side along the line.
+        let mut end = game.clone();
+        let (mut won, mut lost) = (0, 0);
+        for play in &pv {
+            let turn = end.turn;
+            let captures = end.play(play).ok()?.0.len();
+
+            if turn == game.turn {
+                won += captures;
+            } else {
+                lost += captures;
+            }
+        }
+        if won < lost + self.captures {
+            return None;
+        }
+
+        Some(Puzzle {
+            position: Position::from(game),
+            theme: Theme::Capture,
+            solution: pv,
+        })
+    }
+}
+
+/// A search that goes to `depth` plays and no further.
+fn search(depth: u64) -> AiBasic {
+    AiBasic {
+        depth,
+        time_to_move: 60,
+        transposition_table: TranspositionTable::new(4),
+        solver_nodes: 0,
+        ..AiBasic::default()
+    }
+}
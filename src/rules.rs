@@ -0,0 +1,370 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{bitboard::BitBoard, board_size::BoardSize, role::Role};
+
+/// How many rules there are in a `RuleSet`.
+const RULES: usize = 10;
+
+/// The rules that differ between the tafl variants played on the same board.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct RuleSet {
+    /// Where the king has to reach to escape.
+    pub escape: Escape,
+    /// Whether the king helps to capture attackers.
+    pub armed_king: bool,
+    /// How many attackers it takes to capture the king.
+    pub king_capture: KingCapture,
+    /// Whether a row of pieces on the edge is captured by closing both ends.
+    pub shield_walls: bool,
+    /// Whether the defenders win by building a fort on the edge around a king that can move.
+    pub exit_forts: bool,
+    /// Whether the attackers win by surrounding all of the defenders.
+    pub encirclement: bool,
+    /// Who may not play to a position that was already reached.
+    pub repetition: Repetition,
+    /// The game is drawn when the attackers reach the same position this many times.
+    pub repetition_draw: Option<u32>,
+    /// The game is drawn after this many plays in a row without a capture.
+    pub move_limit: Option<u32>,
+    /// Whether the game is drawn when the king is alone and too few attackers are left to
+    /// capture him.
+    pub insufficient_material: bool,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::copenhagen()
+    }
+}
+
+impl RuleSet {
+    #[must_use]
+    pub fn copenhagen() -> Self {
+        Self {
+            escape: Escape::Corner,
+            armed_king: true,
+            king_capture: KingCapture::FourSides,
+            shield_walls: true,
+            exit_forts: true,
+            encirclement: true,
+            repetition: Repetition::Defender,
+            repetition_draw: None,
+            move_limit: None,
+            insufficient_material: false,
+        }
+    }
+
+    #[must_use]
+    pub fn fetlar() -> Self {
+        Self {
+            escape: Escape::Corner,
+            armed_king: true,
+            king_capture: KingCapture::FourSides,
+            shield_walls: false,
+            exit_forts: false,
+            encirclement: false,
+            repetition: Repetition::Neither,
+            repetition_draw: None,
+            move_limit: None,
+            insufficient_material: false,
+        }
+    }
+
+    /// The squares the king wins by reaching.
+    #[must_use]
+    pub fn exits(&self, size: BoardSize) -> BitBoard {
+        let masks = size.masks();
+        match self.escape {
+            Escape::Corner => masks.corners,
+            Escape::Edge => masks.edges,
+        }
+    }
+
+    /// The squares only the king may stop on.
+    #[must_use]
+    pub fn restricted(&self, size: BoardSize) -> BitBoard {
+        let masks = size.masks();
+        match self.escape {
+            Escape::Corner => masks.restricted,
+            Escape::Edge => masks.throne,
+        }
+    }
+
+    /// Whether `role` is banned from playing to a position that was already reached.
+    #[must_use]
+    pub fn forbids_repetition(&self, role: Role) -> bool {
+        match self.repetition {
+            Repetition::Attacker => role == Role::Attacker,
+            Repetition::Defender => role == Role::Defender,
+            Repetition::Both => role != Role::Roleless,
+            Repetition::Neither => false,
+        }
+    }
+
+    /// Whether `attackers` can't capture a king who has no defenders left.
+    #[must_use]
+    pub fn insufficient_material(&self, attackers: u16, defenders: u16) -> bool {
+        let attackers_needed = match self.king_capture {
+            KingCapture::TwoSides => 1,
+            // Next to the throne three attackers are enough.
+            KingCapture::FourSides => 3,
+        };
+
+        self.insufficient_material && defenders == 0 && attackers < attackers_needed
+    }
+
+    /// Changes the rule `rule` names and returns which of the `RULES` rules it is.
+    fn set(&mut self, rule: &str) -> anyhow::Result<usize> {
+        let index = match rule {
+            "armed_king" => {
+                self.armed_king = true;
+                1
+            }
+            "unarmed_king" => {
+                self.armed_king = false;
+                1
+            }
+            "shield_walls" => {
+                self.shield_walls = true;
+                3
+            }
+            "no_shield_walls" => {
+                self.shield_walls = false;
+                3
+            }
+            "exit_forts" => {
+                self.exit_forts = true;
+                4
+            }
+            "no_exit_forts" => {
+                self.exit_forts = false;
+                4
+            }
+            "encirclement" => {
+                self.encirclement = true;
+                5
+            }
+            "no_encirclement" => {
+                self.encirclement = false;
+                5
+            }
+            "no_repetition_draw" => {
+                self.repetition_draw = None;
+                7
+            }
+            "no_move_limit" => {
+                self.move_limit = None;
+                8
+            }
+            "insufficient_material" => {
+                self.insufficient_material = true;
+                9
+            }
+            "no_insufficient_material" => {
+                self.insufficient_material = false;
+                9
+            }
+            rule => {
+                if let Ok(escape) = Escape::from_str(rule) {
+                    self.escape = escape;
+                    0
+                } else if let Ok(king_capture) = KingCapture::from_str(rule) {
+                    self.king_capture = king_capture;
+                    2
+                } else if let Ok(repetition) = Repetition::from_str(rule) {
+                    self.repetition = repetition;
+                    6
+                } else if let Some(Ok(times)) =
+                    rule.strip_prefix("repetition_draw_").map(str::parse)
+                {
+                    self.repetition_draw = Some(times);
+                    7
+                } else if let Some(Ok(plays)) = rule.strip_prefix("move_limit_").map(str::parse) {
+                    self.move_limit = Some(plays);
+                    8
+                } else {
+                    return Err(anyhow::Error::msg(format!("unknown rule: {rule}")));
+                }
+            }
+        };
+
+        Ok(index)
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = if *self == Self::copenhagen() {
+            "copenhagen"
+        } else if *self == Self::fetlar() {
+            "fetlar"
+        } else {
+            "house"
+        };
+
+        write!(
+            f,
+            "{name} {} {} {} {} {} {} {} {} {} {}",
+            self.escape,
+            if self.armed_king {
+                "armed_king"
+            } else {
+                "unarmed_king"
+            },
+            self.king_capture,
+            if self.shield_walls {
+                "shield_walls"
+            } else {
+                "no_shield_walls"
+            },
+            if self.exit_forts {
+                "exit_forts"
+            } else {
+                "no_exit_forts"
+            },
+            if self.encirclement {
+                "encirclement"
+            } else {
+                "no_encirclement"
+            },
+            self.repetition,
+            if let Some(times) = self.repetition_draw {
+                format!("repetition_draw_{times}")
+            } else {
+                "no_repetition_draw".to_string()
+            },
+            if let Some(plays) = self.move_limit {
+                format!("move_limit_{plays}")
+            } else {
+                "no_move_limit".to_string()
+            },
+            if self.insufficient_material {
+                "insufficient_material"
+            } else {
+                "no_insufficient_material"
+            },
+        )
+    }
+}
+
+impl TryFrom<Vec<&str>> for RuleSet {
+    type Error = anyhow::Error;
+
+    /// Parses `rules NAME [RULE ...]`, where each **RULE** changes the named rule set. A `house`
+    /// rule set has no rules of its own, so each of them has to be given.
+    fn try_from(args: Vec<&str>) -> anyhow::Result<Self> {
+        let err_msg = "expected: rules copenhagen|fetlar|house [RULE ...]";
+
+        let (mut rules, house) = match args.get(1) {
+            Some(&"copenhagen") => (Self::copenhagen(), false),
+            Some(&"fetlar") => (Self::fetlar(), false),
+            Some(&"house") => (Self::copenhagen(), true),
+            _ => return Err(anyhow::Error::msg(err_msg)),
+        };
+
+        let mut given = [false; RULES];
+        for rule in args.iter().skip(2) {
+            given[rules.set(rule)?] = true;
+        }
+
+        if house && given.contains(&false) {
+            return Err(anyhow::Error::msg(format!(
+                "rules house: each of the {RULES} rules has to be given"
+            )));
+        }
+
+        Ok(rules)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Escape {
+    Corner,
+    Edge,
+}
+
+impl fmt::Display for Escape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Corner => write!(f, "corner_escape"),
+            Self::Edge => write!(f, "edge_escape"),
+        }
+    }
+}
+
+impl FromStr for Escape {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "corner_escape" => Ok(Self::Corner),
+            "edge_escape" => Ok(Self::Edge),
+            _ => Err(anyhow::Error::msg(format!("invalid escape: {value}"))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum KingCapture {
+    /// The king is captured like any other piece, between two attackers.
+    TwoSides,
+    /// The king is captured by attackers on all four sides, the empty throne counts as one.
+    FourSides,
+}
+
+impl fmt::Display for KingCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TwoSides => write!(f, "king_captured_by_2"),
+            Self::FourSides => write!(f, "king_captured_by_4"),
+        }
+    }
+}
+
+impl FromStr for KingCapture {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "king_captured_by_2" => Ok(Self::TwoSides),
+            "king_captured_by_4" => Ok(Self::FourSides),
+            _ => Err(anyhow::Error::msg(format!("invalid king capture: {value}"))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Repetition {
+    Attacker,
+    Defender,
+    Both,
+    Neither,
+}
+
+impl fmt::Display for Repetition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Attacker => write!(f, "repetition_attacker"),
+            Self::Defender => write!(f, "repetition_defender"),
+            Self::Both => write!(f, "repetition_both"),
+            Self::Neither => write!(f, "repetition_neither"),
+        }
+    }
+}
+
+impl FromStr for Repetition {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "repetition_attacker" => Ok(Self::Attacker),
+            "repetition_defender" => Ok(Self::Defender),
+            "repetition_both" => Ok(Self::Both),
+            "repetition_neither" => Ok(Self::Neither),
+            _ => Err(anyhow::Error::msg(format!("invalid repetition: {value}"))),
+        }
+    }
+}
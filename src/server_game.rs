@@ -10,10 +10,12 @@ use serde::{Deserialize, Serialize};
 use crate::{
     board::Board,
     game::{Game, PreviousBoards},
+    game_end::GameEnd,
     glicko::Rating,
     play::{PlayRecord, Plays},
     rating::Rated,
     role::Role,
+    rules::RuleSet,
     status::Status,
     time::{Time, TimeSettings},
 };
@@ -28,6 +30,9 @@ pub struct ArchivedGame {
     pub rated: Rated,
     pub plays: Vec<PlayRecord>,
     pub status: Status,
+    /// How the game ended, games archived before this was recorded have none.
+    #[serde(default)]
+    pub game_end: Option<GameEnd>,
     pub texts: VecDeque<String>,
 }
 
@@ -43,6 +48,7 @@ impl ArchivedGame {
             rated: game.rated,
             plays: game.game.plays.0,
             status: game.game.status,
+            game_end: game.game.game_end,
             texts: game.texts,
         }
     }
@@ -93,6 +99,7 @@ impl ArchivedGameHandle {
                         &Status::Ongoing,
                         &turn,
                         &mut PreviousBoards::default(),
+                        &RuleSet::default(),
                     )
                     .unwrap();
                 boards.push(board.clone());
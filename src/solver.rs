@@ -0,0 +1,249 @@
+use std::fmt;
+
+use crate::{game::Game, play::Plae, role::Role, status::Status};
+
+/// How many positions `solve` looks at if it isn't told.
+pub const DEFAULT_NODES: u64 = 100_000;
+
+const INFINITY: u64 = u64::MAX;
+
+/// What the solver found for the side to play.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Solution {
+    /// The side to play wins whatever the other side plays, the line is one way it goes.
+    Win(Vec<Plae>),
+    /// The side to play loses whatever it plays, the line is one way it goes.
+    Loss(Vec<Plae>),
+    /// Neither was proven within the budget.
+    Unknown,
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (result, line) = match self {
+            Self::Win(line) => ("win", line),
+            Self::Loss(line) => ("loss", line),
+            Self::Unknown => return write!(f, "unknown"),
+        };
+
+        write!(f, "{result}")?;
+        for play in line {
+            match play {
+                Plae::Play(play) => write!(f, " {}-{}", play.from, play.to)?,
+                Plae::AttackerResigns | Plae::DefenderResigns => write!(f, " resigns")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Looks for a forced win or loss for the side to play with proof number search, spending half
+/// of the `nodes` on each.
+#[must_use]
+pub fn solve(game: &Game, nodes: u64) -> Solution {
+    if let Some(line) = prove(game, game.turn, nodes / 2) {
+        Solution::Win(line)
+    } else if let Some(line) = prove(game, game.turn.opposite(), nodes / 2) {
+        Solution::Loss(line)
+    } else {
+        Solution::Unknown
+    }
+}
+
+/// Returns a line that wins for `prover` whatever the other side plays, if one is found before
+/// the tree reaches `nodes` positions. A draw is a loss for the prover.
+#[must_use]
+pub fn prove(game: &Game, prover: Role, nodes: u64) -> Option<Vec<Plae>> {
+    if game.status != Status::Ongoing {
+        return None;
+    }
+
+    let mut game = game.clone();
+    let mut tree = vec![Node::new(None, None, &game, prover)];
+
+    while tree[0].proof != 0 && tree[0].disproof != 0 && (tree.len() as u64) < nodes {
+        let mut index = 0;
+        let mut undos = Vec::new();
+
+        while tree[index].expanded {
+            let Some(child) = most_proving(&tree, index) else {
+                break;
+            };
+            let Some(play) = &tree[child].play else {
+                break;
+            };
+            let Ok((_captures, undo)) = game.make_untimed(play) else {
+                break;
+            };
+
+            undos.push(undo);
+            index = child;
+        }
+
+        if tree[index].expanded {
+            break;
+        }
+
+        for play in game.all_legal_plays() {
+            if let Ok((_captures, undo)) = game.make_untimed(&play) {
+                tree.push(Node::new(Some(index), Some(play), &game, prover));
+                game.unmake(undo);
+
+                let child = tree.len() - 1;
+                tree[index].children.push(child);
+            }
+        }
+        tree[index].expanded = true;
+
+        let mut node = Some(index);
+        while let Some(index) = node {
+            update(&mut tree, index);
+            node = tree[index].parent;
+        }
+
+        for undo in undos.into_iter().rev() {
+            game.unmake(undo);
+        }
+    }
+
+    if tree[0].proof != 0 {
+        return None;
+    }
+
+    let lengths = line_lengths(&tree);
+    let mut line = Vec::new();
+    let mut index = 0;
+
+    while tree[index].expanded {
+        let solved_children = tree[index]
+            .children
+            .iter()
+            .copied()
+            .filter(|child| tree[*child].proof == 0);
+
+        let next = if tree[index].prover_plays {
+            solved_children.min_by_key(|child| lengths[*child])
+        } else {
+            solved_children.max_by_key(|child| lengths[*child])
+        };
+        let Some(next) = next else {
+            break;
+        };
+
+        line.extend(tree[next].play.clone());
+        index = next;
+    }
+
+    Some(line)
+}
+
+/// A position in the proof tree.
+#[derive(Clone, Debug)]
+struct Node {
+    parent: Option<usize>,
+    /// The play that reached the position.
+    play: Option<Plae>,
+    children: Vec<usize>,
+    expanded: bool,
+    /// Whether the prover plays next, then one winning play proves the node, otherwise every
+    /// play has to.
+    prover_plays: bool,
+    /// How many more positions at least have to be proven to prove this one.
+    proof: u64,
+    /// How many more positions at least have to be disproven to disprove this one.
+    disproof: u64,
+}
+
+impl Node {
+    fn new(parent: Option<usize>, play: Option<Plae>, game: &Game, prover: Role) -> Self {
+        let winner = match game.status {
+            Status::AttackerWins => Some(Role::Attacker),
+            Status::DefenderWins => Some(Role::Defender),
+            Status::Draw => Some(Role::Roleless),
+            Status::Ongoing => None,
+        };
+
+        let (proof, disproof) = match winner {
+            None => (1, 1),
+            Some(winner) if winner == prover => (0, INFINITY),
+            Some(_) => (INFINITY, 0),
+        };
+
+        Self {
+            parent,
+            play,
+            children: Vec::new(),
+            expanded: false,
+            prover_plays: game.turn == prover,
+            proof,
+            disproof,
+        }
+    }
+}
+
+/// The child to look at next, the one that is closest to proving or disproving the node.
+fn most_proving(tree: &[Node], index: usize) -> Option<usize> {
+    let node = &tree[index];
+
+    if node.prover_plays {
+        node.children
+            .iter()
+            .copied()
+            .min_by_key(|child| tree[*child].proof)
+    } else {
+        node.children
+            .iter()
+            .copied()
+            .min_by_key(|child| tree[*child].disproof)
+    }
+}
+
+fn update(tree: &mut [Node], index: usize) {
+    let node = &tree[index];
+    if !node.expanded {
+        return;
+    }
+
+    let proofs = node.children.iter().map(|child| tree[*child].proof);
+    let disproofs = node.children.iter().map(|child| tree[*child].disproof);
+
+    let (proof, disproof) = if node.prover_plays {
+        (
+            proofs.min().unwrap_or(INFINITY),
+            disproofs.fold(0, u64::saturating_add),
+        )
+    } else {
+        (
+            proofs.fold(0, u64::saturating_add),
+            disproofs.min().unwrap_or(INFINITY),
+        )
+    };
+
+    tree[index].proof = proof;
+    tree[index].disproof = disproof;
+}
+
+/// How many plays the proving line takes from each proven position: the quickest win when the
+/// prover plays, and the longest defense when the other side plays.
+fn line_lengths(tree: &[Node]) -> Vec<usize> {
+    let mut lengths = vec![0; tree.len()];
+
+    // The children come after their parent.
+    for index in (0..tree.len()).rev() {
+        let proven = tree[index]
+            .children
+            .iter()
+            .filter(|child| tree[**child].proof == 0)
+            .map(|child| lengths[*child] + 1);
+
+        lengths[index] = if tree[index].prover_plays {
+            proven.min()
+        } else {
+            proven.max()
+        }
+        .unwrap_or_default();
+    }
+
+    lengths
+}
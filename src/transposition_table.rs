@@ -0,0 +1,229 @@
+use std::{
+    fmt,
+    mem::size_of,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{
+    board_size::BoardSize,
+    play::{Plae, Play, Vertex},
+    role::Role,
+};
+
+/// The size of the table if none is chosen, in megabytes.
+pub const DEFAULT_MEGABYTES: usize = 16;
+
+/// The deepest search an entry can record.
+const MAX_DEPTH: u64 = 63;
+
+/// How the stored value relates to the real value of the position.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Bound {
+    /// The value is the real value.
+    Exact,
+    /// The real value is at least the value.
+    Lower,
+    /// The real value is at most the value.
+    Upper,
+}
+
+/// What a search learned about a position.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    /// The Zobrist hash of the position with the side to play.
+    pub key: u64,
+    /// How many plays deep the position was searched, at most 63.
+    pub depth: u64,
+    pub bound: Bound,
+    pub value: i32,
+    pub play: Option<Plae>,
+}
+
+impl Entry {
+    /// Packs everything but the key into 64 bits: the value, bound, depth, play, and board size.
+    fn data(&self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+
+        let (kind, role, from, to, size) = match &self.play {
+            None => (0, 0, 0, 0, 0),
+            Some(Plae::Play(play)) => (
+                1,
+                u64::from(play.role == Role::Defender),
+                index(&play.from),
+                index(&play.to),
+                play.from.size.get() as u64 / 2 - 3,
+            ),
+            Some(Plae::AttackerResigns) => (2, 0, 0, 0, 0),
+            Some(Plae::DefenderResigns) => (3, 0, 0, 0, 0),
+        };
+
+        u64::from(u32::from_ne_bytes(self.value.to_ne_bytes()))
+            | bound << 32
+            | self.depth.min(MAX_DEPTH) << 34
+            | kind << 40
+            | role << 42
+            | from << 43
+            | to << 52
+            | size << 61
+    }
+
+    fn from_data(key: u64, data: u64) -> Option<Self> {
+        let bound = match (data >> 32) & 0b11 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            2 => Bound::Upper,
+            _ => return None,
+        };
+
+        let play = match (data >> 40) & 0b11 {
+            0 => None,
+            1 => {
+                let size = BoardSize::try_from(((data >> 61) as usize + 3) * 2 + 1).ok()?;
+                let role = if (data >> 42) & 1 == 1 {
+                    Role::Defender
+                } else {
+                    Role::Attacker
+                };
+
+                Some(Plae::Play(Play {
+                    role,
+                    from: vertex((data >> 43) & 0x1ff, size)?,
+                    to: vertex((data >> 52) & 0x1ff, size)?,
+                }))
+            }
+            2 => Some(Plae::AttackerResigns),
+            _ => Some(Plae::DefenderResigns),
+        };
+
+        Some(Self {
+            key,
+            depth: (data >> 34) & MAX_DEPTH,
+            bound,
+            #[allow(clippy::cast_possible_truncation)]
+            value: i32::from_ne_bytes((data as u32).to_ne_bytes()),
+            play,
+        })
+    }
+}
+
+/// Search results by Zobrist hash, a newer entry replaces whatever was in its slot.
+///
+/// The table is shared by the search threads without a lock. Each slot holds the entry and the
+/// key xor the entry, so a slot that two threads wrote at once doesn't match any key.
+pub struct TranspositionTable {
+    entries: Vec<[AtomicU64; 2]>,
+}
+
+impl Clone for TranspositionTable {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self
+                .entries
+                .iter()
+                .map(|[check, data]| {
+                    [
+                        AtomicU64::new(check.load(Ordering::Relaxed)),
+                        AtomicU64::new(data.load(Ordering::Relaxed)),
+                    ]
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_MEGABYTES)
+    }
+}
+
+impl fmt::Debug for TranspositionTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TranspositionTable({} entries)", self.entries.len())
+    }
+}
+
+impl TranspositionTable {
+    /// A table that takes up to `megabytes`, it has a power of two number of entries.
+    #[must_use]
+    pub fn new(megabytes: usize) -> Self {
+        let entries = (megabytes * 1024 * 1024 / size_of::<[AtomicU64; 2]>()).max(1);
+        let entries = if entries.is_power_of_two() {
+            entries
+        } else {
+            entries.next_power_of_two() / 2
+        };
+
+        Self {
+            entries: (0..entries)
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+        }
+    }
+
+    pub fn clear(&self) {
+        for [check, data] in &self.entries {
+            check.store(0, Ordering::Relaxed);
+            data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, key: u64) -> Option<Entry> {
+        let [check, data] = &self.entries[self.index(key)];
+        let data = data.load(Ordering::Relaxed);
+
+        // An empty slot holds zeros, which only match the key zero.
+        if check.load(Ordering::Relaxed) ^ data == key && data != 0 {
+            Entry::from_data(key, data)
+        } else {
+            None
+        }
+    }
+
+    pub fn insert(&self, entry: &Entry) {
+        let [check, data] = &self.entries[self.index(entry.key)];
+        let entry_data = entry.data();
+
+        data.store(entry_data, Ordering::Relaxed);
+        check.store(entry.key ^ entry_data, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn index(&self, key: u64) -> usize {
+        // The number of entries is a power of two.
+        key as usize & (self.entries.len() - 1)
+    }
+}
+
+fn index(vertex: &Vertex) -> u64 {
+    (vertex.y * vertex.size.get() + vertex.x) as u64
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn vertex(index: u64, size: BoardSize) -> Option<Vertex> {
+    let index = index as usize;
+    if index >= size.get() * size.get() {
+        return None;
+    }
+
+    Some(Vertex {
+        size,
+        x: index % size.get(),
+        y: index / size.get(),
+    })
+}
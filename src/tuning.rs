@@ -0,0 +1,123 @@
+use crate::{
+    evaluation::{Evaluator, Features},
+    game::Game,
+    play::Plae,
+    status::Status,
+};
+
+/// The plays at the start of a game that aren't sampled, most games share them.
+const OPENING_PLAYS: usize = 8;
+
+/// A quiet position from a finished game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub features: Features,
+    /// How the game ended for the defender: 1 for a win, 0 for a loss, and 0.5 for a draw.
+    pub result: f64,
+}
+
+/// Replays the `plays` of a game that ended with `status` and returns its quiet positions, the
+/// ones where the side to play can't capture a piece and the king can't escape. A game that
+/// didn't end or has an illegal play gives what was sampled before it.
+pub fn samples(plays: impl IntoIterator<Item = Plae>, status: &Status) -> Vec<Sample> {
+    let result = match status {
+        Status::AttackerWins => 0.0,
+        Status::Draw => 0.5,
+        Status::DefenderWins => 1.0,
+        Status::Ongoing => return Vec::new(),
+    };
+
+    let mut game = Game::default();
+    let mut samples = Vec::new();
+
+    for (index, play) in plays.into_iter().enumerate() {
+        if index >= OPENING_PLAYS && game.status == Status::Ongoing {
+            let features = Features::new(&game);
+            let quiet = features.king_escape_routes == 0
+                && !game
+                    .legal_moves()
+                    .any(|play| game.board.captures_a_piece(&play, &game.rules));
+
+            if quiet {
+                samples.push(Sample { features, result });
+            }
+        }
+
+        if game.play(&play).is_err() {
+            break;
+        }
+    }
+
+    samples
+}
+
+/// The chance the defender wins, as the evaluator guesses from `score`.
+#[must_use]
+pub fn sigmoid(score: i32, k: f64) -> f64 {
+    1.0 / (1.0 + (-k * f64::from(score)).exp())
+}
+
+/// The mean squared error of the guesses of `evaluator` for how the games ended.
+#[allow(clippy::cast_precision_loss)]
+#[must_use]
+pub fn error(evaluator: &Evaluator, samples: &[Sample], k: f64) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+
+    let sum: f64 = samples
+        .iter()
+        .map(|sample| (sample.result - sigmoid(evaluator.score(&sample.features), k)).powi(2))
+        .sum();
+
+    sum / samples.len() as f64
+}
+
+/// The scale of the sigmoid that best fits the scores of `evaluator` to the results.
+#[must_use]
+pub fn fit_k(evaluator: &Evaluator, samples: &[Sample]) -> f64 {
+    let mut best = (f64::MAX, 1.0);
+    let mut k = 1e-5;
+
+    while k < 1.0 {
+        let error = error(evaluator, samples, k);
+        if error < best.0 {
+            best = (error, k);
+        }
+        k *= 1.1;
+    }
+
+    best.1
+}
+
+/// Moves each weight by one while that lowers the error, for at most `passes` passes over the
+/// weights, and returns the tuned evaluator with its error.
+#[must_use]
+pub fn tune(evaluator: &Evaluator, samples: &[Sample], k: f64, passes: usize) -> (Evaluator, f64) {
+    let mut best = evaluator.clone();
+    let mut best_error = error(&best, samples, k);
+
+    for _ in 0..passes {
+        let mut improved = false;
+
+        for weight in 0..best.weights_mut().len() {
+            for step in [1, -1] {
+                let mut evaluator = best.clone();
+                *evaluator.weights_mut()[weight] += step;
+
+                let error = error(&evaluator, samples, k);
+                if error < best_error {
+                    (best, best_error) = (evaluator, error);
+                    improved = true;
+                    break;
+                }
+            }
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    (best, best_error)
+}
@@ -0,0 +1,74 @@
+use crate::{board::Board, play::Vertex, role::Role, space::Space};
+
+/// The table every position is hashed with, it is the same every run.
+pub static ZOBRIST: ZobristTable = ZobristTable::new();
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ZobristTable {
+    /// Bitstrings representing piece placement, there is room for a 19×19 board.
+    piece_bits: [[u64; 3]; 19 * 19],
+    /// Bitstring to use used when it's the defender's move.
+    defender_to_move_bits: u64,
+}
+
+impl Default for ZobristTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZobristTable {
+    #[must_use]
+    pub const fn new() -> Self {
+        let mut state = 0x1503_174b;
+        let mut piece_bits = [[0; 3]; 19 * 19];
+
+        let mut i = 0;
+        while i < piece_bits.len() {
+            piece_bits[i] = [
+                split_mix_64(&mut state),
+                split_mix_64(&mut state),
+                split_mix_64(&mut state),
+            ];
+            i += 1;
+        }
+
+        Self {
+            piece_bits,
+            defender_to_move_bits: split_mix_64(&mut state),
+        }
+    }
+
+    /// The key of the board with the side to play mixed in.
+    #[must_use]
+    pub fn hash(&self, board: &Board, side_to_play: Role) -> u64 {
+        board.key ^ self.side_to_play(side_to_play)
+    }
+
+    /// The bits mixed into the key of a board for the side to play.
+    #[must_use]
+    pub fn side_to_play(&self, side_to_play: Role) -> u64 {
+        if side_to_play == Role::Defender {
+            self.defender_to_move_bits
+        } else {
+            0
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If the space is empty.
+    #[must_use]
+    pub fn piece(&self, vertex: &Vertex, space: Space) -> u64 {
+        self.piece_bits[vertex.y * vertex.size.get() + vertex.x][space.index()]
+    }
+}
+
+const fn split_mix_64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}